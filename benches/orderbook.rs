@@ -0,0 +1,131 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use market_datatypes::{OrderPrice, Side};
+use tom_orderbook::{Execution, MakerOrder, OrderBook};
+
+enum Event {
+    Add(MakerOrder),
+    Cancel(u64),
+    Execute(Fill),
+}
+
+struct Fill(u64, i64);
+
+impl Execution for Fill {
+    fn maker_id(&self) -> u64 {
+        self.0
+    }
+    fn size(&self) -> i64 {
+        self.1
+    }
+}
+
+/// xorshift, good enough to spread the orders around
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// adds, cancels and executions around a drifting mid price.
+/// cancels and executions pick a random live order.
+fn synthetic_stream(len: usize) -> Vec<Event> {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut live: Vec<(u64, i64)> = vec![];
+    let mut events = Vec::with_capacity(len);
+    let mut mid = 100_000i64;
+    let mut next_id = 0;
+    while events.len() < len {
+        if rng.below(100) == 0 {
+            mid += rng.below(21) as i64 - 10;
+        }
+        let roll = rng.below(10);
+        if roll < 6 || live.is_empty() {
+            let side = if rng.below(2) == 0 {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            let offset = (rng.below(50) + 1) as i64;
+            let price = match side {
+                Side::Buy => mid - offset,
+                Side::Sell => mid + offset,
+            };
+            let qty = rng.below(20) as i64 + 1;
+            next_id += 1;
+            live.push((next_id, qty));
+            events.push(Event::Add(MakerOrder {
+                id: next_id,
+                price: OrderPrice::Limit(price),
+                qty,
+                side,
+            }));
+        } else {
+            let idx = rng.below(live.len() as u64) as usize;
+            let (id, qty) = live.swap_remove(idx);
+            if roll < 9 {
+                events.push(Event::Cancel(id));
+            } else {
+                events.push(Event::Execute(Fill(id, qty)));
+            }
+        }
+    }
+    events
+}
+
+fn replay(events: &[Event]) -> OrderBook {
+    let mut book = OrderBook::new(0);
+    for event in events {
+        let _ = match event {
+            Event::Add(order) => book.add(order.clone()).map(|_| ()),
+            Event::Cancel(id) => book.remove(*id).map(|_| ()),
+            Event::Execute(fill) => book.execute(fill).map(|_| ()),
+        };
+    }
+    book
+}
+
+fn synthetic(c: &mut Criterion) {
+    let events = synthetic_stream(1_000_000);
+    let mut group = c.benchmark_group("synthetic");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(events.len() as u64));
+    group.bench_function("replay_1m_events", |b| b.iter(|| replay(&events)));
+    group.finish();
+}
+
+fn add_cancel(c: &mut Criterion) {
+    // steady state book to add to and cancel from
+    let events = synthetic_stream(100_000);
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut group = c.benchmark_group("latency");
+    let mut book = replay(&events);
+    group.bench_function("add_then_cancel", |b| {
+        b.iter(|| {
+            let price = 100_000 + rng.below(100) as i64 - 50;
+            book.add(MakerOrder {
+                id: u64::MAX,
+                price: OrderPrice::Limit(price),
+                qty: 1,
+                side: if price < 100_000 {
+                    Side::Buy
+                } else {
+                    Side::Sell
+                },
+            })
+            .unwrap();
+            book.remove(u64::MAX).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, synthetic, add_cancel);
+criterion_main!(benches);
@@ -1,54 +1,65 @@
-use std::{path::PathBuf, collections::HashMap};
+use databento_mbo::{into_maker_order, MboExecution};
+use dbn::{decode::DecodeDbn, enums::Action, record::MboMsg};
+use tom_orderbook::{MakerOrder, OrderBookError, OrderBookRegistry, OrderBookUpdate};
 
-use dbn::{decode::{DecodeDbn, self}, record::{MboMsg, str_to_c_chars, c_chars_to_str, TradeMsg, HasRType}, enums::{flags, Action}, error::ConversionError};
+type Update = OrderBookUpdate<MakerOrder, u64, MboExecution>;
 
-struct Config {
-    data_file: PathBuf
+/// applies the message to the book of its instrument
+fn apply(registry: &mut OrderBookRegistry, msg: &MboMsg) -> Result<(), OrderBookError> {
+    let instrument_id = msg.hd.instrument_id as u64;
+    match Action::try_from(msg.action as u8) {
+        Ok(Action::Add) => {
+            if let Some(order) = into_maker_order(msg) {
+                registry.update(instrument_id, Update::Add(order))?;
+            }
+        }
+        Ok(Action::Modify) => {
+            if let Some(order) = into_maker_order(msg) {
+                registry.update(instrument_id, Update::Update(order, msg.order_id))?;
+            }
+        }
+        // cancels only carry the order id that is routed by the registry.
+        // `size` is the cancelled quantity, the order is removed when nothing is left
+        Ok(Action::Cancel) => {
+            let order = registry
+                .order(msg.order_id)
+                .ok_or(OrderBookError::UnknownOrderId(msg.order_id))?;
+            let remaining = order.qty - msg.size as i64;
+            registry.change_qty(msg.order_id, remaining)?;
+        }
+        Ok(Action::Clear) => {
+            registry.update(instrument_id, Update::Clear)?;
+        }
+        // fills and trades do not change the book,
+        // the cancel or modify that follows a fill reduces the resting order
+        _ => {}
+    }
+    Ok(())
 }
 
 fn main() {
-    let mut file = dbn::decode::dbn::Decoder::from_zstd_file("../proprietary-data/databento/glbx-mdp3-20230221.mbo.dbn.zst").unwrap();
-    let mut hashmap = HashMap::new();
-    let mut orderbooks = HashMap::new();
+    let mut file = dbn::decode::dbn::Decoder::from_zstd_file(
+        "../proprietary-data/databento/glbx-mdp3-20230221.mbo.dbn.zst",
+    )
+    .unwrap();
+    let mut registry = OrderBookRegistry::new();
+    let mut errors = 0;
     loop {
         match file.decode_record::<MboMsg>() {
-            Ok(Some(r)) => {
-                let action = Action::try_from(r.action as u8)
-                .map_err(|_| ConversionError::TypeConversion("Invalid action"));
-                *hashmap.entry(format!("{:?}", action.unwrap())).or_insert(1) += 1;
-            },
-            Ok(None) => {
-                println!("{hashmap:#?}");
-                break
-            },
+            Ok(Some(msg)) => {
+                if let Err(e) = apply(&mut registry, msg) {
+                    if errors == 0 {
+                        println!("first error: {e}");
+                    }
+                    errors += 1;
+                }
+            }
+            Ok(None) => break,
             Err(e) => {
                 println!("{e:#?}");
-                println!("{hashmap:#?}");
-                break
+                break;
             }
         };
-
-        //let action = Action::try_from(i.action as u8).unwrap();
-        //if action == Action::Fill {
-            //println!("{i:#?}")
-        //}
-
-        /*
-        let id = i.order_id as u64;
-        let mut_book = hashmap.entry(i.hd.instrument_id).or_insert_with(|| {
-            OrderBook::new(i.hd.instrument_id as u64)
-        });
-
-        let action = Action::try_from(i.action as u8).unwrap();
-        
-        match action {
-            Action::Add => mut_book.add(order),
-            Action::Cancel => mut_book.remove(&id),
-            Action::Fill => mut_book.replace(add, ),
-        }
-        mut_book.add(order)
-        println!("{action:#?}");
-    */
     }
-    
+    println!("{} books, {errors} errors", registry.len());
 }
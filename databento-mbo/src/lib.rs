@@ -1,8 +1,11 @@
 use std::ffi::c_char;
 
 use dbn::record::MboMsg;
-use market_datatypes::Side;
-use tom_orderbook::{MakerOrder, OrderBook, UniqueOrderId};
+use market_datatypes::{FixedPrice, PriceScale, Side};
+use tom_orderbook::{Execution, MakerOrder, OrderBook, UniqueOrderId};
+
+/// prices of DBN records are fixed-point with 9 decimals
+pub const PRICE_SCALE: PriceScale = PriceScale::new(9);
 
 pub fn into_maker_order(value: &MboMsg) -> Option<MakerOrder> {
     Some(MakerOrder {
@@ -21,3 +24,42 @@ pub fn into_maker_order(value: &MboMsg) -> Option<MakerOrder> {
         id: value.order_id,
     })
 }
+
+/// Fill message of a resting order
+pub struct MboExecution {
+    pub order_id: u64,
+    pub price: i64,
+    pub size: i64,
+}
+
+impl MboExecution {
+    pub fn fixed_price(&self) -> FixedPrice {
+        PRICE_SCALE.price(self.price)
+    }
+}
+
+impl Execution for MboExecution {
+    fn maker_id(&self) -> u64 {
+        self.order_id
+    }
+
+    fn size(&self) -> i64 {
+        self.size
+    }
+
+    fn price(&self) -> Option<i64> {
+        Some(self.price)
+    }
+}
+
+/// returns `None` unless the message is a fill
+pub fn into_execution(value: &MboMsg) -> Option<MboExecution> {
+    match value.action as u8 as char {
+        'F' => Some(MboExecution {
+            order_id: value.order_id,
+            price: value.price,
+            size: value.size as i64,
+        }),
+        _ => None,
+    }
+}
@@ -6,3 +6,5 @@ mod side;
 pub use side::Side;
 mod order_price;
 pub use order_price::OrderPrice;
+mod price_scale;
+pub use price_scale::{FixedPrice, PriceScale};
@@ -1,8 +1,10 @@
-use std::{convert::Infallible, default};
+use num_traits::Bounded;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 
 
 
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub enum OrderPrice<P = i64> 
     where
         P: Copy + Clone,
@@ -40,22 +42,12 @@ impl TryFrom<OrderPrice> for f64 {
 
 
 
-impl OrderPrice<i64> {
-    pub fn price_min_if_market(&self) -> i64 {
+impl<P: Copy + Bounded> OrderPrice<P> {
+    pub fn price_min_if_market(&self) -> P {
         if let OrderPrice::Limit(i) = self {
             *i
         } else {
-            i64::MIN
+            P::min_value()
         }
     }
 }
-
-impl OrderPrice<f64> {
-    pub fn price_min_if_market(&self) -> f64 {
-        if let OrderPrice::Limit(i) = self {
-            *i
-        } else {
-            f64::MIN
-        }
-    }
-}
\ No newline at end of file
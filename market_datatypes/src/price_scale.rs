@@ -0,0 +1,247 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of decimals of integer prices, the price is `value * 10^-decimals`
+///
+/// every feed stores prices as integers with its own scale,
+/// e.g. MOEX uses 5 decimals and Databento 9.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub struct PriceScale {
+    decimals: u8,
+}
+
+/// more decimals do not fit into an `i64`
+const MAX_DECIMALS: u8 = 18;
+
+impl PriceScale {
+    /// panics if `decimals` is larger than 18
+    pub const fn new(decimals: u8) -> Self {
+        assert!(
+            decimals <= MAX_DECIMALS,
+            "at most 18 decimals are supported"
+        );
+        Self { decimals }
+    }
+
+    /// `None` if `decimals` is negative or larger than 18
+    pub fn try_new(decimals: i64) -> Option<Self> {
+        let decimals = u8::try_from(decimals).ok()?;
+        (decimals <= MAX_DECIMALS).then_some(Self { decimals })
+    }
+
+    pub const fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// `10^decimals`, the integer value of a price of 1
+    pub const fn unit(&self) -> i64 {
+        10i64.pow(self.decimals as u32)
+    }
+
+    /// the value with this scale
+    pub const fn price(&self, value: i64) -> FixedPrice {
+        FixedPrice {
+            value,
+            scale: *self,
+        }
+    }
+
+    /// nearest `f64` of the price
+    pub fn to_f64(&self, value: i64) -> f64 {
+        value as f64 / self.unit() as f64
+    }
+
+    /// rounds the price to the nearest value of this scale, `None` if it does not fit into an `i64`
+    pub fn from_f64(&self, price: f64) -> Option<i64> {
+        let value = (price * self.unit() as f64).round();
+        (value.is_finite() && value >= i64::MIN as f64 && value < i64::MAX as f64)
+            .then_some(value as i64)
+    }
+
+    /// converts the value to another scale, `None` if digits would be lost or it overflows
+    pub fn rescale(&self, value: i64, to: PriceScale) -> Option<i64> {
+        match to.decimals.cmp(&self.decimals) {
+            Ordering::Equal => Some(value),
+            Ordering::Greater => value.checked_mul(10i64.pow((to.decimals - self.decimals) as u32)),
+            Ordering::Less => {
+                let div = 10i64.pow((self.decimals - to.decimals) as u32);
+                (value % div == 0).then_some(value / div)
+            }
+        }
+    }
+
+    /// exact decimal string of the value, e.g. `-1.05000` for -105000 with 5 decimals
+    pub fn format(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let decimals = self.decimals as usize;
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (int, frac) = digits.split_at(digits.len() - decimals);
+        let sign = if value < 0 { "-" } else { "" };
+        if decimals == 0 {
+            format!("{sign}{int}")
+        } else {
+            format!("{sign}{int}.{frac}")
+        }
+    }
+
+    /// parses a decimal string exactly, `None` if it has more significant decimals
+    /// than the scale or does not fit into an `i64`
+    pub fn parse(&self, s: &str) -> Option<i64> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        if int.is_empty() && frac.is_empty() {
+            return None;
+        }
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(int) || !is_digits(frac) {
+            return None;
+        }
+        let decimals = self.decimals as usize;
+        let (kept, dropped) = frac.split_at(frac.len().min(decimals));
+        if dropped.bytes().any(|b| b != b'0') {
+            return None;
+        }
+        let mut value: i128 = 0;
+        for b in int.bytes().chain(kept.bytes()) {
+            value = value.checked_mul(10)?.checked_add((b - b'0') as i128)?;
+        }
+        value *= 10i128.pow((decimals - kept.len()) as u32);
+        if negative {
+            value = -value;
+        }
+        i64::try_from(value).ok()
+    }
+}
+
+/// Integer price together with its `PriceScale`
+///
+/// prices of different scales compare by their exact value.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct FixedPrice {
+    pub value: i64,
+    pub scale: PriceScale,
+}
+
+impl FixedPrice {
+    pub const fn new(value: i64, scale: PriceScale) -> Self {
+        Self { value, scale }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.scale.to_f64(self.value)
+    }
+
+    /// the same price with another scale, `None` if digits would be lost or it overflows
+    pub fn rescale(&self, to: PriceScale) -> Option<Self> {
+        Some(Self {
+            value: self.scale.rescale(self.value, to)?,
+            scale: to,
+        })
+    }
+
+    /// value scaled to 18 decimals
+    fn exact(&self) -> i128 {
+        self.value as i128 * 10i128.pow((MAX_DECIMALS - self.scale.decimals) as u32)
+    }
+}
+
+impl PartialEq for FixedPrice {
+    fn eq(&self, other: &Self) -> bool {
+        self.exact() == other.exact()
+    }
+}
+
+impl Eq for FixedPrice {}
+
+impl PartialOrd for FixedPrice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FixedPrice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.exact().cmp(&other.exact())
+    }
+}
+
+impl fmt::Display for FixedPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.scale.format(self.value))
+    }
+}
+
+/// the scale is the number of decimals of the string
+impl FromStr for FixedPrice {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let decimals = s.split_once('.').map_or(0, |(_, frac)| frac.len());
+        let scale = PriceScale::try_new(decimals as i64).ok_or(())?;
+        Ok(Self {
+            value: scale.parse(s).ok_or(())?,
+            scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format_and_parse() {
+        let scale = PriceScale::new(5);
+        assert_eq!(scale.format(7375000000), "73750.00000");
+        assert_eq!(scale.format(-105000), "-1.05000");
+        assert_eq!(scale.format(5), "0.00005");
+        assert_eq!(PriceScale::new(0).format(-12), "-12");
+        assert_eq!(scale.format(i64::MIN), "-92233720368547.75808");
+
+        assert_eq!(scale.parse("73750.00000"), Some(7375000000));
+        assert_eq!(scale.parse("73750"), Some(7375000000));
+        assert_eq!(scale.parse("-1.05"), Some(-105000));
+        assert_eq!(scale.parse(".5"), Some(50000));
+        assert_eq!(scale.parse("1.0000050"), None);
+        assert_eq!(scale.parse("1.000000"), Some(100000));
+        assert_eq!(scale.parse("1e5"), None);
+        assert_eq!(scale.parse("."), None);
+        assert_eq!(scale.parse("99999999999999999"), None);
+    }
+
+    #[test]
+    fn conversions() {
+        let moex = PriceScale::new(5);
+        let databento = PriceScale::new(9);
+        assert_eq!(moex.to_f64(7375012345), 73750.12345);
+        assert_eq!(moex.from_f64(73750.12345), Some(7375012345));
+        assert_eq!(moex.from_f64(f64::NAN), None);
+        assert_eq!(moex.rescale(7375012345, databento), Some(73750123450000));
+        assert_eq!(databento.rescale(73750123450000, moex), Some(7375012345));
+        assert_eq!(databento.rescale(73750123450001, moex), None);
+        assert_eq!(PriceScale::new(0).rescale(i64::MAX, databento), None);
+        assert_eq!(PriceScale::try_new(19), None);
+        assert_eq!(PriceScale::try_new(-1), None);
+    }
+
+    #[test]
+    fn fixed_price() {
+        let a = PriceScale::new(5).price(7375000000);
+        let b: FixedPrice = "73750.0".parse().unwrap();
+        assert_eq!(b.scale.decimals(), 1);
+        assert_eq!(a, b);
+        assert!(a < "73750.000000001".parse().unwrap());
+        assert_eq!(a.rescale(PriceScale::new(0)).unwrap().to_string(), "73750");
+        assert_eq!(b.to_string(), "73750.0");
+        assert_eq!(a.to_f64(), 73750.);
+        assert!("1.2.3".parse::<FixedPrice>().is_err());
+    }
+}
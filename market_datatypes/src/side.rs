@@ -42,4 +42,13 @@ impl Side {
     pub fn is_sell(&self) -> bool {
         !self.is_buy()
     }
-}
\ No newline at end of file
+
+    /// side of the counterparty
+    #[inline]
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Buy => Self::Sell,
+            Self::Sell => Self::Buy,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use moex::{DerivativeOrderLog, MoexOrderLogReader, MoexReplayer};
+
+/// applies the order log to one book per symbol and returns the number of rejected rows
+fn replay(logs: &[DerivativeOrderLog]) -> usize {
+    let mut replayer = MoexReplayer::new();
+    logs.iter()
+        .filter(|log| replayer.apply(log).is_err())
+        .count()
+}
+
+fn moex_5000(c: &mut Criterion) {
+    let file = include_str!("../test-data/5000.txt");
+    // a row that stops parsing fails the bench instead of shrinking its input
+    let logs: Vec<_> = MoexOrderLogReader::new(file.as_bytes())
+        .map(|log| log.unwrap_or_else(|e| panic!("5000.txt does not parse: {e}")))
+        .collect();
+    let mut group = c.benchmark_group("moex");
+    group.throughput(Throughput::Elements(logs.len() as u64));
+    group.bench_function("replay_5000_txt", |b| b.iter(|| replay(&logs)));
+    group.finish();
+}
+
+criterion_group!(benches, moex_5000);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+use std::str::{FromStr, Split};
+
+use crate::MoexParseError;
+
+/// Comma separated columns of a row, errors name the column they happened in
+pub(crate) struct Columns<'a> {
+    iter: Split<'a, char>,
+    /// index of the next column
+    column: usize,
+}
+
+impl<'a> Columns<'a> {
+    pub(crate) fn new(row: &'a str) -> Self {
+        Self {
+            iter: row.trim_end_matches(['\n', '\r']).split(','),
+            column: 0,
+        }
+    }
+
+    pub(crate) fn field(&mut self, field: &'static str) -> Result<&'a str, MoexParseError> {
+        let value = self.iter.next();
+        self.column += 1;
+        value.ok_or(MoexParseError {
+            field,
+            column: self.column - 1,
+            value: None,
+        })
+    }
+
+    pub(crate) fn parse<T: FromStr>(&mut self, field: &'static str) -> Result<T, MoexParseError> {
+        self.parse_with(field, |s| s.parse().ok())
+    }
+
+    pub(crate) fn parse_with<T>(
+        &mut self,
+        field: &'static str,
+        f: impl FnOnce(&str) -> Option<T>,
+    ) -> Result<T, MoexParseError> {
+        let value = self.field(field)?;
+        f(value).ok_or_else(|| self.invalid(field, value))
+    }
+
+    /// error for the value of the last column
+    pub(crate) fn invalid(&self, field: &'static str, value: &str) -> MoexParseError {
+        MoexParseError {
+            field,
+            column: self.column - 1,
+            value: Some(value.to_string()),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use market_datatypes::Side;
+
+use crate::crate_prelude::*;
+
+/// One row of a deal, i.e. the execution of one of its orders
+#[derive(Debug, Clone, PartialEq)]
+pub struct DealLeg {
+    /// ID_DEAL
+    pub deal_id: i64,
+    pub timestamp: NaiveDateTime,
+    pub symbol: String,
+    pub order_id: u64,
+    pub side: Side,
+    /// PRICE_DEAL
+    pub price: OrderPrice,
+    pub volume: i64,
+}
+
+impl DealLeg {
+    /// `None` unless the row is an `Action::Trade`
+    pub fn new(log: &DerivativeOrderLog) -> Option<Self> {
+        match log.action {
+            Action::Trade(deal) => Some(Self {
+                deal_id: deal.id,
+                timestamp: log.timestamp,
+                symbol: log.name.clone(),
+                order_id: log.id,
+                side: log.side,
+                price: deal.price,
+                volume: log.volume,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Deal between a buy and a sell order, assembled from its two `DealLeg`s
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deal {
+    pub deal_id: i64,
+    pub timestamp: NaiveDateTime,
+    pub symbol: String,
+    pub buy_order: u64,
+    pub sell_order: u64,
+    pub price: OrderPrice,
+    pub volume: i64,
+    /// side of the newer order, order ids increase with the arrival of the orders
+    pub aggressor: Side,
+}
+
+impl Deal {
+    /// volume signed by the aggressor, positive if the buyer initiated the deal
+    pub fn signed_volume(&self) -> i64 {
+        match self.aggressor {
+            Side::Buy => self.volume,
+            Side::Sell => -self.volume,
+        }
+    }
+}
+
+/// The two legs of a deal do not describe the same deal
+#[derive(Debug, Clone, PartialEq)]
+pub struct DealMismatch {
+    pub first: Box<DealLeg>,
+    pub second: Box<DealLeg>,
+}
+
+impl fmt::Display for DealMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "legs of deal {} do not match: order {} and order {}",
+            self.first.deal_id, self.first.order_id, self.second.order_id
+        )
+    }
+}
+
+impl std::error::Error for DealMismatch {}
+
+/// Pairs the rows of a deal into a `Deal`
+///
+/// MOEX logs a deal once for each of its orders with the same ID_DEAL.
+/// the first leg waits until its counterpart arrives, legs whose counterpart never arrives
+/// are reported by `take_unmatched` and `into_unmatched`.
+#[derive(Default)]
+pub struct DealAssembler {
+    pending: HashMap<i64, DealLeg>,
+}
+
+impl DealAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// returns the deal once both legs arrived, rows other than `Action::Trade` are ignored.
+    /// legs on the same side, or with a different symbol, price or volume are a `DealMismatch`
+    /// and both are dropped.
+    pub fn push(&mut self, log: &DerivativeOrderLog) -> Result<Option<Deal>, DealMismatch> {
+        match DealLeg::new(log) {
+            Some(leg) => self.push_leg(leg),
+            None => Ok(None),
+        }
+    }
+
+    pub fn push_leg(&mut self, leg: DealLeg) -> Result<Option<Deal>, DealMismatch> {
+        let Some(first) = self.pending.remove(&leg.deal_id) else {
+            self.pending.insert(leg.deal_id, leg);
+            return Ok(None);
+        };
+        if first.side == leg.side
+            || first.symbol != leg.symbol
+            || first.price != leg.price
+            || first.volume != leg.volume
+        {
+            return Err(DealMismatch {
+                first: Box::new(first),
+                second: Box::new(leg),
+            });
+        }
+        let (buy, sell) = match first.side {
+            Side::Buy => (&first, &leg),
+            Side::Sell => (&leg, &first),
+        };
+        let aggressor = if buy.order_id > sell.order_id {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        Ok(Some(Deal {
+            deal_id: first.deal_id,
+            timestamp: first.timestamp,
+            buy_order: buy.order_id,
+            sell_order: sell.order_id,
+            price: first.price,
+            volume: first.volume,
+            aggressor,
+            symbol: first.symbol,
+        }))
+    }
+
+    /// legs that wait for their counterpart, in no particular order
+    pub fn pending(&self) -> impl Iterator<Item = &DealLeg> {
+        self.pending.values()
+    }
+
+    /// removes the legs older than `before`, their counterpart is not expected anymore
+    pub fn take_unmatched(&mut self, before: NaiveDateTime) -> Vec<DealLeg> {
+        let stale: Vec<_> = self
+            .pending
+            .values()
+            .filter(|leg| leg.timestamp < before)
+            .map(|leg| leg.deal_id)
+            .collect();
+        let mut unmatched: Vec<_> = stale
+            .iter()
+            .filter_map(|id| self.pending.remove(id))
+            .collect();
+        unmatched.sort_by_key(|leg| (leg.timestamp, leg.deal_id));
+        unmatched
+    }
+
+    /// legs that are still waiting at the end of the log, sorted by time
+    pub fn into_unmatched(self) -> Vec<DealLeg> {
+        let mut unmatched: Vec<_> = self.pending.into_values().collect();
+        unmatched.sort_by_key(|leg| (leg.timestamp, leg.deal_id));
+        unmatched
+    }
+}
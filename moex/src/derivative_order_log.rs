@@ -1,8 +1,9 @@
 use std::convert::Infallible;
 
-use tom_orderbook::{MakerOrder, UniqueOrderId};
+use tom_orderbook::{Execution, MakerOrder, UniqueOrderId};
 
 pub use crate::crate_prelude::*;
+use crate::columns::Columns;
 
 #[derive(Debug, PartialEq)]
 pub struct DerivativeOrderLog {
@@ -23,36 +24,35 @@ pub struct DerivativeOrderLog {
 // number of decimals is `.00000`
 impl DerivativeOrderLog {
     // #SYMBOL,SYSTEM,TYPE,MOMENT,ID,ACTION,PRICE,VOLUME,ID_DEAL,PRICE_DEAL
-    pub fn new(s: &str) -> Option<Self> {
-        let timestamp_fmt = "%Y%m%d%H%M%S%f";
-        let mut iter = s.split(",");
-        let func = |s: &str| s.chars().into_iter().filter(|i| i != &'.').collect::<String>();
+    pub fn new(s: &str) -> Result<Self, MoexParseError> {
+        let timestamp_fmt = "%Y%m%d%H%M%S%3f";
+        let mut columns = Columns::new(s);
         // name of the variables matches the `field name` written on the specification
-        let symbol = iter.next()?;
-        let system: DerivativeType = iter.next()?.try_into().ok()?;
-        let side = match iter.next()? {
-            "B" => Side::Buy,
-            "S" => Side::Sell,
-            _ => return None,
-        };
-        let timestamp = NaiveDateTime::parse_from_str(iter.next()?, timestamp_fmt).ok()?;
-        let id = iter.next()?.parse().ok()?;
-        let action_byte = iter.next()?;
-        let price = {
-            func(&iter.next()?).parse::<i64>().ok()?.into()
-        };
-        let volume = iter.next()?.parse::<i64>().ok()?;
-        let action = match iter.next()? {
-            "" if action_byte == "0" => Action::Cancel,
-            "" if action_byte == "1" => Action::Add,
-            trade_id => {
-                let price = func(&iter.next()?).parse::<i64>().ok()?.into();
-                let id = trade_id.parse::<i64>().ok()?;
+        let symbol = columns.field("SYMBOL")?;
+        let system: DerivativeType = columns.parse_with("SYSTEM", |s| s.try_into().ok())?;
+        let side = columns.parse_with("TYPE", |s| match s {
+            "B" => Some(Side::Buy),
+            "S" => Some(Side::Sell),
+            _ => None,
+        })?;
+        let timestamp = columns.parse_with("MOMENT", |s| {
+            NaiveDateTime::parse_from_str(s, timestamp_fmt).ok()
+        })?;
+        let id = columns.parse("ID")?;
+        let action_byte: u8 = columns.parse_with("ACTION", |s| s.parse().ok().filter(|b| *b <= 2))?;
+        let price = columns.parse_with("PRICE", |s| PRICE_SCALE.parse(s))?.into();
+        let volume = columns.parse("VOLUME")?;
+        let action = match action_byte {
+            0 => Action::Cancel,
+            1 => Action::Add,
+            _ => {
+                let id = columns.parse("ID_DEAL")?;
+                let price = columns.parse_with("PRICE_DEAL", |s| PRICE_SCALE.parse(s))?.into();
                 Action::Trade(TradeLog { price, id })
             }
         };
 
-        Some(DerivativeOrderLog {
+        Ok(DerivativeOrderLog {
             name: symbol.to_string(),
             derivative_type: system,
             side,
@@ -64,13 +64,26 @@ impl DerivativeOrderLog {
         })
     }
     pub fn price_f64(&self) -> f64 {
+        self.fixed_price().map_or(f64::NAN, |p| p.to_f64())
+    }
+
+    /// `None` for market orders
+    pub fn fixed_price(&self) -> Option<FixedPrice> {
         match self.price {
-            OrderPrice::Limit(i) => i as f64 / 100000.,
-            OrderPrice::Market => f64::NAN,
+            OrderPrice::Limit(i) => Some(PRICE_SCALE.price(i)),
+            OrderPrice::Market => None,
         }
     }
 }
 
+impl FromStr for DerivativeOrderLog {
+    type Err = MoexParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
 impl TryFrom<DerivativeOrderLog> for MakerOrder {
     type Error = Infallible;
     fn try_from(value: DerivativeOrderLog) -> Result<Self, Self::Error> {
@@ -83,10 +96,32 @@ impl TryFrom<DerivativeOrderLog> for MakerOrder {
     }
 }
 
-impl TryFrom<DerivativeOrderLog> for UniqueOrderId {
-    type Error = Infallible;
-    fn try_from(value: DerivativeOrderLog) -> Result<Self, Self::Error> {
-        Ok(UniqueOrderId::new(value.id))
+impl UniqueOrderId for DerivativeOrderLog {
+    fn unique_order_id(&self) -> u64 {
+        self.id
+    }
+}
+
+/// only rows with `Action::Trade` are executions.
+/// MOEX logs both sides of a deal. the aggressing order is added right before the deal,
+/// so both rows execute an order that rests on the book, see `MoexReplayer`.
+impl Execution for DerivativeOrderLog {
+    fn maker_id(&self) -> u64 {
+        self.id
+    }
+
+    fn size(&self) -> i64 {
+        self.volume
+    }
+
+    fn price(&self) -> Option<i64> {
+        match self.action {
+            Action::Trade(TradeLog {
+                price: OrderPrice::Limit(price),
+                ..
+            }) => Some(price),
+            _ => None,
+        }
     }
 }
 
@@ -103,14 +138,14 @@ mod test {
         let opts = DerivativeOrderLog::new(
             "Si73750BC2,C,B,20220131185256610,1892947028292403201,1,1.00000,1,,",
         );
-        assert!(opts.is_some());
+        assert!(opts.is_ok());
         let opts = DerivativeOrderLog::new(
             "Si77500BN2A,P,B,20220131190608307,1892947028292437532,2,213.00000,1,1892947028292405803,213.00000"
         );
         assert_eq!(
             opts.unwrap(),
             DerivativeOrderLog {
-                timestamp: NaiveDateTime::from_str("2022-01-31T19:06:08.000000307").unwrap(),
+                timestamp: NaiveDateTime::from_str("2022-01-31T19:06:08.307").unwrap(),
                 side: market_datatypes::Side::Buy,
                 id: 1892947028292437532,
                 action: crate::Action::Trade(
@@ -1,53 +1,145 @@
+use chrono::{NaiveDate, NaiveTime};
+use market_datatypes::Side;
+use tom_orderbook::{Execution, MakerOrder, OrderBookUpdate, UniqueOrderId};
+
+use crate::columns::Columns;
+use crate::crate_prelude::*;
+
+#[derive(Debug, PartialEq)]
 pub struct EquityOrderLog {
-    no: i32,
-    seccode: String,
-    buysell: Side,
-    time: NaiveDateTime,
-    orderno: i32,
-    action: Action,
-    price: Price,
-    volume: i64,
+    /// NO, sequence number of the row
+    pub no: u64,
+    /// SECCODE
+    pub seccode: String,
+    /// BUYSELL
+    pub side: Side,
+    /// TIME, the file of a trading day has no date
+    pub time: NaiveTime,
+    /// ORDERNO
+    pub id: u64,
+    pub action: Action,
+    /// `Market` if PRICE is 0
+    pub price: OrderPrice,
+    pub volume: i64,
 }
 
+// number of decimals is `EQUITY_PRICE_SCALE`
 impl EquityOrderLog {
-    pub fn new(s: &str) -> Option<Self> {
-        let mut iter = s.split(",");
-        let timestamp_fmt = "%Y%m%d%H%M%S%f";
-
-        let _sequence_number = iter.next()?.parse::<i64>().ok()?;
-        let sec_code = iter.next()?.to_string();
-        let buy_sell = Side::from_str(iter.next()?).ok()?;
-        let time = NaiveDateTime::parse_from_str(iter.next()?, timestamp_fmt).ok()?;
-        let order_no = iter.next()?.parse::<i64>().ok()?;
-        let action_byte = iter.next()?;
-        let price = {
-            let n = iter.next()?;
-            let decimal = iter.next()?.parse::<Decimal>().ok()?;
-            if n == "0" {
-                Price::Market
-            } else {
-                Price::Limit(decimal)
-            }
+    // NO,SECCODE,BUYSELL,TIME,ORDERNO,ACTION,PRICE,VOLUME,TRADENO,TRADEPRICE
+    pub fn new(s: &str) -> Result<Self, MoexParseError> {
+        let mut columns = Columns::new(s);
+        let no = columns.parse("NO")?;
+        let seccode = columns.field("SECCODE")?.to_string();
+        let side = columns.parse_with("BUYSELL", |s| match s {
+            "B" => Some(Side::Buy),
+            "S" => Some(Side::Sell),
+            _ => None,
+        })?;
+        // HHMMSSZZZ, newer files have microseconds
+        let time = columns.parse_with("TIME", |s| {
+            NaiveTime::parse_from_str(s, "%H%M%S%3f")
+                .or_else(|_| NaiveTime::parse_from_str(s, "%H%M%S%6f"))
+                .ok()
+        })?;
+        let id = columns.parse("ORDERNO")?;
+        let action_byte: u8 =
+            columns.parse_with("ACTION", |s| s.parse().ok().filter(|b| *b <= 2))?;
+        let price = match columns.parse_with("PRICE", |s| EQUITY_PRICE_SCALE.parse(s))? {
+            0 => OrderPrice::Market,
+            price => OrderPrice::Limit(price),
         };
-        let volume = iter.next()?.parse::<i64>().ok()?;
-        let action = match iter.next()? {
-            "" if action_byte == "0" => Action::Cancel,
-            "" if action_byte == "1" => Action::Add,
-            trade_id => {
-                let price = iter.next()?.parse::<i64>().ok()?;
-                let id = trade_id.parse::<i64>().ok()?;
-                Action::Trade(TradeLog { price, id })
+        let volume = columns.parse("VOLUME")?;
+        let action = match action_byte {
+            0 => Action::Cancel,
+            1 => Action::Add,
+            _ => {
+                let id = columns.parse("TRADENO")?;
+                let price = columns.parse_with("TRADEPRICE", |s| EQUITY_PRICE_SCALE.parse(s))?;
+                Action::Trade(TradeLog {
+                    price: OrderPrice::Limit(price),
+                    id,
+                })
             }
         };
-        Some(Self {
+        Ok(Self {
             no,
             seccode,
-            buysell,
+            side,
             time,
-            orderno,
+            id,
             action,
             price,
             volume,
         })
     }
+
+    pub fn timestamp(&self, date: NaiveDate) -> NaiveDateTime {
+        date.and_time(self.time)
+    }
+
+    /// `None` for market orders
+    pub fn fixed_price(&self) -> Option<FixedPrice> {
+        match self.price {
+            OrderPrice::Limit(i) => Some(EQUITY_PRICE_SCALE.price(i)),
+            OrderPrice::Market => None,
+        }
+    }
+
+    /// adds, cancels or executes the order of the row.
+    /// a cancel removes the whole remaining order.
+    pub fn into_update(self) -> OrderBookUpdate<MakerOrder, u64, Self> {
+        match self.action {
+            Action::Add => OrderBookUpdate::Add(self.into()),
+            Action::Cancel => OrderBookUpdate::Delete(self.id),
+            Action::Trade(_) => OrderBookUpdate::Execution(self),
+        }
+    }
+}
+
+impl FromStr for EquityOrderLog {
+    type Err = MoexParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl From<EquityOrderLog> for MakerOrder {
+    fn from(value: EquityOrderLog) -> Self {
+        Self {
+            id: value.id,
+            price: value.price,
+            qty: value.volume,
+            side: value.side,
+        }
+    }
+}
+
+impl UniqueOrderId for EquityOrderLog {
+    fn unique_order_id(&self) -> u64 {
+        self.id
+    }
+}
+
+/// only rows with `Action::Trade` are executions.
+/// both orders of a deal are logged. the aggressing order has its ACTION 1 row before the deal,
+/// so it is on the book when its trade row is applied, like the resting order.
+impl Execution for EquityOrderLog {
+    fn maker_id(&self) -> u64 {
+        self.id
+    }
+
+    fn size(&self) -> i64 {
+        self.volume
+    }
+
+    fn price(&self) -> Option<i64> {
+        match self.action {
+            Action::Trade(TradeLog {
+                price: OrderPrice::Limit(price),
+                ..
+            }) => Some(price),
+            _ => None,
+        }
+    }
 }
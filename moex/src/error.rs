@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// Column of an order log row that could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoexParseError {
+    /// name of the column in the header, e.g. `MOMENT`
+    pub field: &'static str,
+    /// index of the column, starts at 0
+    pub column: usize,
+    /// the raw value, `None` if the row ended before the column
+    pub value: Option<String>,
+}
+
+impl fmt::Display for MoexParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(
+                f,
+                "invalid {} in column {}: {:?}",
+                self.field, self.column, value
+            ),
+            None => write!(f, "missing {} in column {}", self.field, self.column),
+        }
+    }
+}
+
+impl std::error::Error for MoexParseError {}
+
+/// Error of reading one row of an order log file
+#[derive(Debug)]
+pub struct MoexReadError {
+    /// line number in the file, starts at 1
+    pub line: usize,
+    pub kind: MoexReadErrorKind,
+}
+
+#[derive(Debug)]
+pub enum MoexReadErrorKind {
+    Parse(MoexParseError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for MoexReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            MoexReadErrorKind::Parse(e) => write!(f, "line {}: {e}", self.line),
+            MoexReadErrorKind::Io(e) => write!(f, "line {}: {e}", self.line),
+        }
+    }
+}
+
+impl std::error::Error for MoexReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            MoexReadErrorKind::Parse(e) => Some(e),
+            MoexReadErrorKind::Io(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::crate_prelude::*;
+
+/// futures month codes, January first
+const FUTURES_MONTHS: &[u8; 12] = b"FGHJKMNQUVXZ";
+
+/// FORTS instrument decoded from its short code, e.g. `Si73750BC2` or `SiH2`
+///
+/// option codes are `<underlying><strike><settlement><month><year>[<week>]`.
+/// the month code also tells the type, `A`-`L` are calls and `M`-`X` are puts from January to December.
+/// weekly options end with `A`-`E` for the week of the month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoexInstrument {
+    /// code of the underlying, e.g. `Si`
+    pub underlying: String,
+    pub kind: DerivativeType,
+    /// `None` for futures, the scale is the number of decimals of the code
+    pub strike: Option<FixedPrice>,
+    /// settlement type code of options, e.g. `B`
+    pub settlement: Option<char>,
+    /// month of the expiry, 1 is January
+    pub month: u32,
+    /// last digit of the year of the expiry
+    pub year_digit: u8,
+    /// week of the month of weekly options, 1 is `A`
+    pub week: Option<u8>,
+}
+
+impl MoexInstrument {
+    pub fn new(code: &str) -> Result<Self, MoexParseError> {
+        let invalid = || MoexParseError {
+            field: "SYMBOL",
+            column: 0,
+            value: Some(code.to_string()),
+        };
+        let bytes = code.as_bytes();
+        let underlying_len = bytes.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+        let rest = &bytes[underlying_len..];
+        match rest {
+            // <underlying><month><year>
+            [year] if underlying_len >= 2 => {
+                let month = FUTURES_MONTHS
+                    .iter()
+                    .position(|m| *m == bytes[underlying_len - 1])
+                    .ok_or_else(invalid)?;
+                Ok(Self {
+                    underlying: code[..underlying_len - 1].to_string(),
+                    kind: DerivativeType::Future,
+                    strike: None,
+                    settlement: None,
+                    month: month as u32 + 1,
+                    year_digit: year_digit(*year).ok_or_else(invalid)?,
+                    week: None,
+                })
+            }
+            _ if underlying_len == 0 => Err(invalid()),
+            _ => {
+                let strike_len = rest
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit() || **b == b'.' || **b == b'-')
+                    .count();
+                let (settlement, month, year, week) = match rest[strike_len..] {
+                    [s, m, y] => (s, m, y, None),
+                    [s, m, y, w] => (s, m, y, Some(w)),
+                    _ => return Err(invalid()),
+                };
+                let strike = code[underlying_len..underlying_len + strike_len]
+                    .parse()
+                    .map_err(|_| invalid())?;
+                let (kind, month) = match month {
+                    b'A'..=b'L' => (DerivativeType::Call, month - b'A'),
+                    b'M'..=b'X' => (DerivativeType::Put, month - b'M'),
+                    _ => return Err(invalid()),
+                };
+                let week = match week {
+                    Some(w @ b'A'..=b'E') => Some(w - b'A' + 1),
+                    None => None,
+                    Some(_) => return Err(invalid()),
+                };
+                if !settlement.is_ascii_uppercase() {
+                    return Err(invalid());
+                }
+                Ok(Self {
+                    underlying: code[..underlying_len].to_string(),
+                    kind,
+                    strike: Some(strike),
+                    settlement: Some(settlement as char),
+                    month: month as u32 + 1,
+                    year_digit: year_digit(year).ok_or_else(invalid)?,
+                    week,
+                })
+            }
+        }
+    }
+
+    pub fn is_option(&self) -> bool {
+        self.strike.is_some()
+    }
+
+    /// year of the expiry, the first year from `today` on that ends with `year_digit`
+    pub fn year(&self, today: NaiveDate) -> i32 {
+        let year = today.year();
+        year + (self.year_digit as i32 - year.rem_euclid(10)).rem_euclid(10)
+    }
+
+    /// series of the option, `None` for futures
+    pub fn series(&self) -> Option<OptionSeries> {
+        self.is_option().then(|| OptionSeries {
+            underlying: self.underlying.clone(),
+            year_digit: self.year_digit,
+            month: self.month,
+            week: self.week,
+        })
+    }
+}
+
+fn year_digit(b: u8) -> Option<u8> {
+    b.is_ascii_digit().then(|| b - b'0')
+}
+
+impl FromStr for MoexInstrument {
+    type Err = MoexParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Options on the same underlying with the same expiry
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OptionSeries {
+    pub underlying: String,
+    pub year_digit: u8,
+    pub month: u32,
+    /// `None` for the monthly series
+    pub week: Option<u8>,
+}
+
+/// groups the option codes by series, every chain is sorted by strike price, calls before puts.
+/// futures and codes that can not be decoded are left out.
+pub fn option_chains<'a>(
+    codes: impl IntoIterator<Item = &'a str>,
+) -> BTreeMap<OptionSeries, Vec<(&'a str, MoexInstrument)>> {
+    let mut chains: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for code in codes {
+        let Ok(instrument) = MoexInstrument::new(code) else {
+            continue;
+        };
+        if let Some(series) = instrument.series() {
+            chains.entry(series).or_default().push((code, instrument));
+        }
+    }
+    for chain in chains.values_mut() {
+        chain.sort_by_key(|(_, i)| (i.strike, i.kind == DerivativeType::Put));
+    }
+    chains
+}
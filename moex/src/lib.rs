@@ -5,6 +5,14 @@ pub(crate) mod crate_prelude {
     pub(crate) use market_datatypes::*;
     
 }
+/// prices of the derivative order logs have 5 decimals
+pub const PRICE_SCALE: market_datatypes::PriceScale = market_datatypes::PriceScale::new(5);
+/// prices of the equity order logs, the smallest tick on the market is 0.000005
+pub const EQUITY_PRICE_SCALE: market_datatypes::PriceScale = market_datatypes::PriceScale::new(6);
+
+mod error;
+pub use error::{MoexParseError, MoexReadError, MoexReadErrorKind};
+mod columns;
 mod order_book_id;
 pub use order_book_id::OrderBookId;
 mod derivative_type;
@@ -19,6 +27,16 @@ mod trade_log;
 pub use trade_log::TradeLog;
 mod derivative_order_log;
 pub use derivative_order_log::DerivativeOrderLog;
+mod equity_order_log;
+pub use equity_order_log::EquityOrderLog;
+mod replayer;
+pub use replayer::{MoexReplayer, MoexTrade};
+mod deal;
+pub use deal::{Deal, DealAssembler, DealLeg, DealMismatch};
+mod reader;
+pub use reader::MoexOrderLogReader;
+mod instrument;
+pub use instrument::{option_chains, MoexInstrument, OptionSeries};
 
 
 #[cfg(test)]
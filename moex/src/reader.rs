@@ -0,0 +1,81 @@
+use std::io::BufRead;
+
+use crate::{DerivativeOrderLog, MoexReadError, MoexReadErrorKind};
+
+/// Reads the rows of a derivative order log file
+///
+/// the `#SYMBOL,...` header and blank lines are skipped.
+/// errors carry the line number and do not stop the iteration.
+pub struct MoexOrderLogReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    skip_bad_rows: bool,
+    bad_rows: usize,
+}
+
+impl<R: BufRead> MoexOrderLogReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+            skip_bad_rows: false,
+            bad_rows: 0,
+        }
+    }
+
+    /// rows that can not be parsed are counted by `bad_rows` instead of being yielded.
+    /// I/O errors are always yielded.
+    pub fn skip_bad_rows(mut self, skip: bool) -> Self {
+        self.skip_bad_rows = skip;
+        self
+    }
+
+    /// number of the last line that was read, starts at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// number of rows that could not be parsed so far, skipped or not
+    pub fn bad_rows(&self) -> usize {
+        self.bad_rows
+    }
+}
+
+impl<R: BufRead> Iterator for MoexOrderLogReader<R> {
+    type Item = Result<DerivativeOrderLog, MoexReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            let read = self.reader.read_line(&mut self.buf);
+            if matches!(read, Ok(0)) {
+                return None;
+            }
+            self.line += 1;
+            if let Err(e) = read {
+                return Some(Err(MoexReadError {
+                    line: self.line,
+                    kind: MoexReadErrorKind::Io(e),
+                }));
+            }
+            let row = self.buf.trim_end_matches(['\n', '\r']);
+            if row.is_empty() || row.starts_with('#') {
+                continue;
+            }
+            match DerivativeOrderLog::new(row) {
+                Ok(log) => return Some(Ok(log)),
+                Err(e) => {
+                    self.bad_rows += 1;
+                    if !self.skip_bad_rows {
+                        return Some(Err(MoexReadError {
+                            line: self.line,
+                            kind: MoexReadErrorKind::Parse(e),
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
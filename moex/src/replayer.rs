@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, HashMap};
+
+use market_datatypes::Side;
+use tom_orderbook::{MakerOrder, OrderBook, OrderBookError, Trade};
+
+use crate::crate_prelude::*;
+
+/// Execution of a resting order together with the side that initiated the deal
+///
+/// MOEX logs every deal twice, once for each order, so every deal yields two `MoexTrade`s
+/// with the same `deal_id` and `aggressor`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoexTrade {
+    pub timestamp: NaiveDateTime,
+    /// ID_DEAL
+    pub deal_id: i64,
+    pub trade: Trade,
+    pub aggressor: Side,
+}
+
+struct SymbolBook {
+    book: OrderBook<MakerOrder>,
+    /// the aggressing order of a deal is added right before its executions
+    last_added: Option<u64>,
+}
+
+/// Replays `DerivativeOrderLog`s into one `OrderBook` per symbol
+///
+/// the aggressing order is added to the book before the exchange reports the deal,
+/// so both orders of a deal rest on the book when their executions arrive.
+/// the books use `CrossingPolicy::Rest` and may be crossed in between.
+#[derive(Default)]
+pub struct MoexReplayer {
+    books: HashMap<String, SymbolBook>,
+}
+
+impl MoexReplayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds, cancels or executes the order of the row.
+    /// returns the trade for `Action::Trade` rows.
+    pub fn apply(&mut self, log: &DerivativeOrderLog) -> Result<Option<MoexTrade>, OrderBookError> {
+        let next_id = self.books.len() as u64;
+        let symbol = match self.books.get_mut(log.name.as_str()) {
+            Some(symbol) => symbol,
+            None => self.books.entry(log.name.clone()).or_insert(SymbolBook {
+                book: OrderBook::new(next_id),
+                last_added: None,
+            }),
+        };
+        match log.action {
+            Action::Add => {
+                symbol.book.add(MakerOrder {
+                    id: log.id,
+                    price: log.price,
+                    qty: log.volume,
+                    side: log.side,
+                })?;
+                symbol.last_added = Some(log.id);
+                Ok(None)
+            }
+            Action::Cancel => {
+                symbol.book.remove(log.id)?;
+                Ok(None)
+            }
+            Action::Trade(deal) => {
+                let trade = symbol.book.execute(log)?;
+                let aggressor = if symbol.last_added == Some(log.id) {
+                    log.side
+                } else {
+                    log.side.opposite()
+                };
+                Ok(Some(MoexTrade {
+                    timestamp: log.timestamp,
+                    deal_id: deal.id,
+                    trade,
+                    aggressor,
+                }))
+            }
+        }
+    }
+
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook<MakerOrder>> {
+        self.books.get(symbol).map(|symbol| &symbol.book)
+    }
+
+    /// iterates the books in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &OrderBook<MakerOrder>)> {
+        self.books
+            .iter()
+            .map(|(name, symbol)| (name.as_str(), &symbol.book))
+    }
+
+    /// option books grouped by series, see `option_chains`
+    pub fn option_chains(&self) -> BTreeMap<OptionSeries, Vec<(&str, MoexInstrument)>> {
+        option_chains(self.books.keys().map(String::as_str))
+    }
+
+    /// number of symbols
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+}
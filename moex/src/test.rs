@@ -1,8 +1,15 @@
 use std::collections::HashMap;
+use std::str::FromStr;
 
-use tom_orderbook::OrderBook;
+use chrono::NaiveDateTime;
 
-use crate::{DerivativeOrderLog, OrderBookId, TradeLog};
+use market_datatypes::OrderPrice::Limit;
+use tom_orderbook::{MakerOrder, OrderBook, PriceQty, Trade};
+
+use crate::{
+    DealAssembler, DerivativeOrderLog, EquityOrderLog, MoexOrderLogReader, MoexParseError,
+    MoexInstrument, MoexReadErrorKind, MoexReplayer, OptionSeries, TradeLog,
+};
 
 #[test]
 fn opt_log_deal() {
@@ -16,20 +23,316 @@ fn opt_log_deal() {
 #[test]
 fn order_book() {
     let file = include_str!("../test-data/5000.txt");
-    
-    let mut hashmap = HashMap::new();
-    for i in file.split("\n").skip(1) {
-        let log  =DerivativeOrderLog::new(i).unwrap();
-        let id = hashmap.len() as u64;
-        let mut book = OrderBook::new(id);
-        hashmap.insert(log.name, hashmap.len());
-        match log.action {
-            crate::Action::Add => (),
-            crate::Action::Cancel => (),
-            crate::Action::Trade(log) => {
-                (   )
-            }
+    let mut replayer = MoexReplayer::new();
+    let mut trades = vec![];
+    for i in file.lines().skip(1) {
+        let log = DerivativeOrderLog::new(i).unwrap();
+        trades.extend(replayer.apply(&log).unwrap());
+    }
+    assert_eq!(replayer.len(), 1263);
+    assert_eq!(replayer.iter().map(|(_, book)| book.order_count()).sum::<usize>(), 4891);
+
+    // 14 deals, each logged for both orders
+    assert_eq!(trades.len(), 28);
+    let buys = trades
+        .iter()
+        .filter(|t| t.aggressor == market_datatypes::Side::Buy)
+        .count();
+    assert_eq!(buys, 26);
+    // BR87BN2A: the sell order 1960782497679682750 arrived after the buy it traded with
+    assert_eq!(
+        trades[6].trade,
+        Trade {
+            maker_id: 1960782497679682750,
+            side: market_datatypes::Side::Sell,
+            price: Limit(78000),
+            qty: 5,
+            remaining_qty: 0,
+        }
+    );
+    assert_eq!(trades[6].deal_id, 1960782497679671689);
+    assert_eq!(trades[6].aggressor, market_datatypes::Side::Sell);
+    assert_eq!(trades[7].aggressor, market_datatypes::Side::Sell);
+
+    let depth = |name: &str, n: usize| {
+        let depth = replayer.book(name).unwrap().depth(n);
+        let levels = |levels: Vec<PriceQty>| -> Vec<_> {
+            levels.iter().map(|i| (i.price(), i.qty())).collect()
+        };
+        (levels(depth.bids), levels(depth.asks))
+    };
+    // the buy order 1960782497679682763 traded 15 of 20 and the rest was cancelled
+    assert_eq!(
+        depth("BR84BN2", 5),
+        (
+            vec![(Limit(181000), 15), (Limit(12000), 20)],
+            vec![(Limit(800000), 10), (Limit(3000000), 1)],
+        )
+    );
+    // the sell order 1960782497679673535 was filled by two deals
+    assert_eq!(
+        depth("BR92BB2A", 5),
+        (
+            vec![(Limit(43000), 5)],
+            vec![(Limit(61000), 5), (Limit(83000), 10), (Limit(123000), 10)],
+        )
+    );
+    assert_eq!(
+        depth("Si64000BO2", 3),
+        (
+            vec![(Limit(300000), 45), (Limit(200000), 100), (Limit(100000), 700)],
+            vec![(Limit(1300000), 195), (Limit(4900000), 1), (Limit(5000000), 21)],
+        )
+    );
+    assert_eq!(replayer.book("Si64000BO2").unwrap().order_count(), 122);
+    // the sell order at 0.33 was cancelled
+    assert_eq!(
+        depth("BR84BN2A", 5),
+        (
+            vec![(Limit(12000), 5)],
+            vec![(Limit(28000), 5), (Limit(30000), 5), (Limit(99000), 1)],
+        )
+    );
+}
+
+#[test]
+fn equity_order_log() {
+    let file = include_str!("../test-data/equity.test.txt");
+    let logs: Vec<_> = file
+        .lines()
+        .skip(1)
+        .map(|i| EquityOrderLog::new(i).unwrap())
+        .collect();
+    assert_eq!(logs.len(), 12);
+    assert_eq!(
+        logs[4],
+        EquityOrderLog {
+            no: 5,
+            seccode: "SBER".to_string(),
+            side: market_datatypes::Side::Buy,
+            time: chrono::NaiveTime::from_hms_milli_opt(10, 0, 0, 10).unwrap(),
+            id: 1,
+            action: crate::Action::Trade(TradeLog {
+                price: market_datatypes::OrderPrice::Limit(270500000),
+                id: 2937001,
+            }),
+            price: market_datatypes::OrderPrice::Limit(270500000),
+            volume: 4,
         }
+    );
+    assert_eq!(logs[7].fixed_price().unwrap().to_string(), "0.023455");
+    assert_eq!(logs[8].price, market_datatypes::OrderPrice::Market);
+    assert_eq!(
+        EquityOrderLog::new("1,SBER,B,100000000,1,3,270.5,10,,")
+            .unwrap_err()
+            .column,
+        5
+    );
+    assert_eq!(
+        EquityOrderLog::new("1,SBER,B,100000000,1,1,270.1234567,10,,")
+            .unwrap_err()
+            .field,
+        "PRICE"
+    );
+
+    let mut books: HashMap<String, OrderBook<MakerOrder>> = HashMap::new();
+    for log in logs {
+        let book = books.entry(log.seccode.clone()).or_default();
+        book.update(log.into_update()).unwrap();
     }
-    
-}
\ No newline at end of file
+    let top = |name: &str| {
+        let book = &books[name];
+        (
+            book.best_bid().map(|i| (i.price(), i.qty())),
+            book.best_ask().map(|i| (i.price(), i.qty())),
+            book.order_count(),
+        )
+    };
+    assert_eq!(
+        top("SBER"),
+        (Some((Limit(270500000), 6)), Some((Limit(271250000), 3)), 2)
+    );
+    assert_eq!(top("GAZP"), (Some((Limit(230120000), 50)), None, 1));
+    assert_eq!(top("VTBR"), (Some((Limit(23455), 10000)), None, 1));
+}
+
+#[test]
+fn deal_assembler() {
+    let file = include_str!("../test-data/5000.txt");
+    let mut assembler = DealAssembler::new();
+    let mut replayer = MoexReplayer::new();
+    let mut deals = vec![];
+    let mut trades = vec![];
+    for i in file.lines().skip(1) {
+        let log = DerivativeOrderLog::new(i).unwrap();
+        deals.extend(assembler.push(&log).unwrap());
+        trades.extend(replayer.apply(&log).unwrap());
+    }
+    assert_eq!(deals.len(), 14);
+    assert_eq!(assembler.pending().count(), 0);
+    // the newer order agrees with the order the replayer saw added last
+    for deal in &deals {
+        for trade in trades.iter().filter(|t| t.deal_id == deal.deal_id) {
+            assert_eq!(trade.aggressor, deal.aggressor);
+        }
+    }
+    assert_eq!(
+        deals[3],
+        crate::Deal {
+            deal_id: 1960782497679671689,
+            timestamp: deals[3].timestamp,
+            symbol: "BR87BN2A".to_string(),
+            buy_order: 1960782497679682745,
+            sell_order: 1960782497679682750,
+            price: Limit(78000),
+            volume: 5,
+            aggressor: market_datatypes::Side::Sell,
+        }
+    );
+    assert_eq!(deals.iter().map(|d| d.signed_volume()).sum::<i64>(), 2 + 2 + 3 - 5 + 15 * 10);
+
+    let mut assembler = DealAssembler::new();
+    let leg = |s: &str| DerivativeOrderLog::new(s).unwrap();
+    let buy = "BR92BB2A,C,B,20220131190000293,1960782497679681544,2,0.49000,2,1960782497679671343,0.44000";
+    let sell = "BR92BB2A,C,S,20220131190000293,1960782497679673535,2,0.44000,2,1960782497679671343,0.44000";
+    let late = "BR93BB2A,C,B,20220131190005000,1960782497679681545,2,0.30000,2,1960782497679671344,0.30000";
+    assert_eq!(assembler.push(&leg(buy)), Ok(None));
+    assert_eq!(assembler.push(&leg(late)), Ok(None));
+    // the counterpart of `buy` never arrives, its deal id is reused by a buy
+    let mismatch = assembler.push(&leg(buy)).unwrap_err();
+    assert_eq!(mismatch.second.side, market_datatypes::Side::Buy);
+    assert_eq!(assembler.push(&leg(sell)), Ok(None));
+    let before = NaiveDateTime::from_str("2022-01-31T19:00:01").unwrap();
+    let unmatched = assembler.take_unmatched(before);
+    assert_eq!(unmatched.len(), 1);
+    assert_eq!(unmatched[0].order_id, 1960782497679673535);
+    let unmatched = assembler.into_unmatched();
+    assert_eq!(unmatched.len(), 1);
+    assert_eq!(unmatched[0].deal_id, 1960782497679671344);
+}
+
+#[test]
+fn order_log_reader() {
+    let file = include_bytes!("../test-data/5000.txt");
+    let mut reader = MoexOrderLogReader::new(&file[..]);
+    assert_eq!(reader.by_ref().filter(|log| log.is_ok()).count(), 4999);
+    assert_eq!(reader.line(), 5000);
+    assert_eq!(reader.bad_rows(), 0);
+
+    assert_eq!(
+        "Si73750BC2,C,B,20220131185256610,1892947028292403201,1,1.00000,1,,".parse::<DerivativeOrderLog>(),
+        DerivativeOrderLog::new("Si73750BC2,C,B,20220131185256610,1892947028292403201,1,1.00000,1,,"),
+    );
+    let rows = "\
+#SYMBOL,SYSTEM,TYPE,MOMENT,ID,ACTION,PRICE,VOLUME,ID_DEAL,PRICE_DEAL
+Si73750BC2,C,B,20220131185256610,1892947028292403201,1,1.00000,1,,
+Si74250BO2,P,X,20220131185256620,1892947028292403202,1,1.00000,1,,
+
+Si74250BO2,P,B,20220131185256620,1892947028292403202,1,1.00000,1,,
+Si74250BO2,P,B,2022-01-31,1892947028292403203,1,1.00000,1,,
+Si74250BO2,P,B,20220131185256620,1892947028292403204,7,1.00000,1,,
+Si74250BO2,P,B,20220131185256620,1892947028292403205,1,1.0000001,1,,
+BR92BB2A,C,B,20220131190000293,1960782497679681544,2,0.49000,2,,
+BR92BB2A,C,B,20220131190000293,1960782497679681544,2,0.49000,2
+Si74250BO2,P,B,20220131185256620,1892947028292403206,1,1.00000,1,,
+";
+    let errors: Vec<_> = MoexOrderLogReader::new(rows.as_bytes())
+        .filter_map(Result::err)
+        .map(|e| match e.kind {
+            MoexReadErrorKind::Parse(parse) => (e.line, parse),
+            MoexReadErrorKind::Io(e) => panic!("{e}"),
+        })
+        .collect();
+    let error = |field, column, value: Option<&str>| MoexParseError {
+        field,
+        column,
+        value: value.map(String::from),
+    };
+    assert_eq!(
+        errors,
+        vec![
+            (3, error("TYPE", 2, Some("X"))),
+            (6, error("MOMENT", 3, Some("2022-01-31"))),
+            (7, error("ACTION", 5, Some("7"))),
+            (8, error("PRICE", 6, Some("1.0000001"))),
+            (9, error("ID_DEAL", 8, Some(""))),
+            (10, error("ID_DEAL", 8, None)),
+        ]
+    );
+    assert_eq!(
+        errors[0].1.to_string(),
+        "invalid TYPE in column 2: \"X\""
+    );
+
+    let mut reader = MoexOrderLogReader::new(rows.as_bytes()).skip_bad_rows(true);
+    let ids: Vec<_> = reader.by_ref().map(|log| log.unwrap().id).collect();
+    assert_eq!(
+        ids,
+        vec![
+            1892947028292403201,
+            1892947028292403202,
+            1892947028292403206
+        ]
+    );
+    assert_eq!(reader.bad_rows(), 6);
+}
+
+#[test]
+fn instrument_code() {
+    let instrument: MoexInstrument = "Si77500BN2A".parse().unwrap();
+    assert_eq!(
+        instrument,
+        MoexInstrument {
+            underlying: "Si".to_string(),
+            kind: crate::DerivativeType::Put,
+            strike: Some("77500".parse().unwrap()),
+            settlement: Some('B'),
+            month: 2,
+            year_digit: 2,
+            week: Some(1),
+        }
+    );
+    let today = chrono::NaiveDate::from_ymd_opt(2022, 1, 31).unwrap();
+    assert_eq!(instrument.year(today), 2022);
+    assert_eq!(
+        MoexInstrument::new("Si73750BC2").unwrap().series(),
+        Some(OptionSeries {
+            underlying: "Si".to_string(),
+            year_digit: 2,
+            month: 3,
+            week: None,
+        })
+    );
+    let ed = MoexInstrument::new("ED1.115BO2").unwrap();
+    assert_eq!(ed.strike.unwrap().to_string(), "1.115");
+    assert_eq!(ed.month, 3);
+
+    let future = MoexInstrument::new("SiH2").unwrap();
+    assert_eq!(future.kind, crate::DerivativeType::Future);
+    assert_eq!((future.underlying.as_str(), future.month), ("Si", 3));
+    let later = chrono::NaiveDate::from_ymd_opt(2029, 1, 1).unwrap();
+    assert_eq!(future.year(later), 2032);
+    assert_eq!(future.series(), None);
+
+    for code in ["", "73750BC2", "Si73750BY2", "Si73750BC2F", "SiI2", "Si7.3.7BC2", "Si73750BCX"] {
+        let error = MoexInstrument::new(code).unwrap_err();
+        assert_eq!(error.value.as_deref(), Some(code));
+    }
+
+    // every code of the sample decodes to the type of its SYSTEM column
+    let file = include_str!("../test-data/5000.txt");
+    let mut replayer = MoexReplayer::new();
+    for log in MoexOrderLogReader::new(file.as_bytes()) {
+        let log = log.unwrap();
+        assert_eq!(MoexInstrument::new(&log.name).unwrap().kind, log.derivative_type);
+        replayer.apply(&log).unwrap();
+    }
+    let chains = replayer.option_chains();
+    assert_eq!(chains.values().map(Vec::len).sum::<usize>(), replayer.len());
+    let series = MoexInstrument::new("BR92BB2A").unwrap().series().unwrap();
+    let chain: Vec<_> = chains[&series].iter().map(|(code, _)| *code).collect();
+    assert_eq!(chain.len(), 36);
+    assert_eq!(chain[..3], ["BR72BN2A", "BR73BN2A", "BR74BN2A"]);
+    assert_eq!(chain[11..14], ["BR84BN2A", "BR85BB2A", "BR85BN2A"]);
+    assert_eq!(chain[35], "BR101BB2A");
+}
@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use osaka::{MessageEnum, MessageRef};
+
+/// the sample lines of the parser tests, panics if one of them does not parse
+fn samples() -> Vec<&'static str> {
+    include_str!("../src/datatypes/test.rs")
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .filter_map(|line| line.strip_prefix('"')?.strip_suffix('"'))
+        .inspect(|line| {
+            if let Err(e) = MessageRef::from_line(line, 0) {
+                panic!("sample {line:?} does not parse: {e}");
+            }
+        })
+        .collect()
+}
+
+fn parse(c: &mut Criterion) {
+    let lines = samples();
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Elements(lines.len() as u64));
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(MessageEnum::from_line(line, 0).unwrap());
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(MessageRef::from_line(line, 0).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! converts a text tag file to the binary encoding
+//!
+//! cargo run --example to_binary -- <input> <output>
+use std::fs::File;
+use std::io::BufWriter;
+
+use osaka::{
+    convert_to_binary,
+    OsakaReader,
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(input), Some(output)) = (args.next(), args.next()) else {
+        eprintln!("usage: to_binary <input> <output>");
+        std::process::exit(2);
+    };
+    let reader = OsakaReader::open(&input).unwrap();
+    let writer = BufWriter::new(File::create(&output).unwrap());
+    let conversion = convert_to_binary(reader, writer).unwrap();
+    for e in &conversion.errors {
+        eprintln!("{e}");
+    }
+    println!(
+        "{} messages written, {} lines skipped",
+        conversion.messages,
+        conversion.errors.len()
+    );
+}
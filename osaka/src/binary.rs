@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::io::{
+    self,
+    BufRead,
+    Read,
+    Write,
+};
+
+use chrono::NaiveDateTime;
+
+use crate::{
+    AddOrder,
+    CombinationProduct,
+    DeleteOrder,
+    EquilibriumPrice,
+    Executed,
+    ExecutionWithPriceInfo,
+    FinancialProduct,
+    LegPrice,
+    MessageEnum,
+    OsakaReader,
+    ParseError,
+    ParseErrorKind,
+    ProductInfo,
+    PutOrCall,
+    SecondTag,
+    Side,
+    SystemEventInfo,
+    TickSize,
+    TradingStatusInfo,
+};
+
+const MAGIC: [u8; 4] = *b"JGTB";
+/// version of the encoding, written after the magic bytes
+pub const BINARY_VERSION: u8 = 1;
+/// record that adds the next string to the symbol table
+const SYMBOL: u8 = 0;
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Writes messages in the compact binary encoding
+///
+/// the stream starts with `JGTB` and `BINARY_VERSION`, then one record per message:
+/// the tag byte, the timestamp in nanoseconds and the fields in a fixed layout,
+/// integers are little endian. repeated strings like symbols are interned,
+/// the first use of a string is preceded by a record that adds it to the symbol table.
+pub struct BinaryWriter<W: Write> {
+    writer: W,
+    encoder: Encoder,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[BINARY_VERSION])?;
+        Ok(Self {
+            writer,
+            encoder: Encoder::default(),
+        })
+    }
+
+    pub fn write(&mut self, msg: &MessageEnum) -> io::Result<()> {
+        let enc = &mut self.encoder;
+        enc.record.clear();
+        enc.symbol_records.clear();
+        enc.record.push(msg.tag() as u8);
+        let known = enc.symbols.len();
+        let encoded = match msg {
+            MessageEnum::AddOrder(x) => x.encode(enc),
+            MessageEnum::CombinationProduct(x) => x.encode(enc),
+            MessageEnum::DeleteOrder(x) => x.encode(enc),
+            MessageEnum::EquilibriumPrice(x) => x.encode(enc),
+            MessageEnum::Executed(x) => x.encode(enc),
+            MessageEnum::ExecutionWithPriceInfo(x) => x.encode(enc),
+            MessageEnum::LegPrice(x) => x.encode(enc),
+            MessageEnum::ProductInfo(x) => x.encode(enc),
+            MessageEnum::SecondTag(x) => x.encode(enc),
+            MessageEnum::SystemEventInfo(x) => x.encode(enc),
+            MessageEnum::TickSize(x) => x.encode(enc),
+            MessageEnum::TradingStatusInfo(x) => x.encode(enc),
+        };
+        if let Err(e) = encoded {
+            // the symbol records of a failed record are never written
+            enc.symbols.retain(|_, id| (*id as usize) < known);
+            return Err(e);
+        }
+        self.writer.write_all(&enc.symbol_records)?;
+        self.writer.write_all(&enc.record)
+    }
+
+    /// flushes and returns the writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads messages written by `BinaryWriter`
+pub struct BinaryReader<R: Read> {
+    decoder: Decoder<R>,
+}
+
+impl<R: Read> BinaryReader<R> {
+    /// reads the header, fails if the version is not `BINARY_VERSION`
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(invalid_data("not a J-GATE binary file".to_string()));
+        }
+        if header[4] != BINARY_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported version {}, expected {BINARY_VERSION}",
+                header[4]
+            )));
+        }
+        Ok(Self {
+            decoder: Decoder {
+                reader,
+                symbols: vec![],
+            },
+        })
+    }
+
+    fn read(&mut self) -> io::Result<Option<MessageEnum>> {
+        let dec = &mut self.decoder;
+        loop {
+            let mut tag = [0];
+            match dec.reader.read_exact(&mut tag) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            let msg = match tag[0] as char {
+                AddOrder::TAG => MessageEnum::AddOrder(Box::new(AddOrder::decode(dec)?)),
+                CombinationProduct::TAG => {
+                    MessageEnum::CombinationProduct(Box::new(CombinationProduct::decode(dec)?))
+                }
+                DeleteOrder::TAG => MessageEnum::DeleteOrder(Box::new(DeleteOrder::decode(dec)?)),
+                EquilibriumPrice::TAG => {
+                    MessageEnum::EquilibriumPrice(Box::new(EquilibriumPrice::decode(dec)?))
+                }
+                Executed::TAG => MessageEnum::Executed(Box::new(Executed::decode(dec)?)),
+                ExecutionWithPriceInfo::TAG => MessageEnum::ExecutionWithPriceInfo(Box::new(
+                    ExecutionWithPriceInfo::decode(dec)?,
+                )),
+                LegPrice::TAG => MessageEnum::LegPrice(Box::new(LegPrice::decode(dec)?)),
+                ProductInfo::TAG => MessageEnum::ProductInfo(Box::new(ProductInfo::decode(dec)?)),
+                SecondTag::TAG => MessageEnum::SecondTag(Box::new(SecondTag::decode(dec)?)),
+                SystemEventInfo::TAG => {
+                    MessageEnum::SystemEventInfo(Box::new(SystemEventInfo::decode(dec)?))
+                }
+                TickSize::TAG => MessageEnum::TickSize(Box::new(TickSize::decode(dec)?)),
+                TradingStatusInfo::TAG => {
+                    MessageEnum::TradingStatusInfo(Box::new(TradingStatusInfo::decode(dec)?))
+                }
+                _ if tag[0] == SYMBOL => {
+                    let symbol = dec.text()?;
+                    dec.symbols.push(symbol);
+                    continue;
+                }
+                _ => return Err(invalid_data(format!("unknown record tag {}", tag[0]))),
+            };
+            return Ok(Some(msg));
+        }
+    }
+}
+
+impl<R: Read> Iterator for BinaryReader<R> {
+    type Item = io::Result<MessageEnum>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
+
+/// Result of `convert_to_binary`
+#[derive(Debug, Default)]
+pub struct Conversion {
+    /// number of messages written
+    pub messages: u64,
+    /// lines that could not be parsed, they are not written
+    pub errors: Vec<ParseError>,
+}
+
+/// converts a text tag file to the binary encoding.
+/// lines that cannot be parsed are skipped and reported, io errors abort the conversion.
+pub fn convert_to_binary<R: BufRead, W: Write>(
+    reader: OsakaReader<R>,
+    writer: W,
+) -> io::Result<Conversion> {
+    let mut writer = BinaryWriter::new(writer)?;
+    let mut conversion = Conversion::default();
+    for msg in reader {
+        match msg {
+            Ok(msg) => {
+                writer.write(&msg)?;
+                conversion.messages += 1;
+            }
+            Err(ParseError {
+                kind: ParseErrorKind::Io(e),
+                ..
+            }) => return Err(e),
+            Err(e) => conversion.errors.push(e),
+        }
+    }
+    writer.into_inner()?;
+    Ok(conversion)
+}
+
+#[derive(Default)]
+struct Encoder {
+    /// the record that is being encoded
+    record: Vec<u8>,
+    /// symbol records for the strings the record uses for the first time
+    symbol_records: Vec<u8>,
+    symbols: HashMap<String, u32>,
+}
+
+impl Encoder {
+    fn timestamp(&mut self, ts: &NaiveDateTime) -> io::Result<()> {
+        let ns = ts.and_utc().timestamp_nanos_opt().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("{ts} is out of range"))
+        })?;
+        self.int(&ns)
+    }
+
+    fn int(&mut self, i: &i64) -> io::Result<()> {
+        self.record.extend_from_slice(&i.to_le_bytes());
+        Ok(())
+    }
+
+    fn side(&mut self, side: &Side) -> io::Result<()> {
+        self.record.push(if side.is_buy() { b'B' } else { b'S' });
+        Ok(())
+    }
+
+    fn flag(&mut self, flag: &bool) -> io::Result<()> {
+        self.record.push(*flag as u8);
+        Ok(())
+    }
+
+    fn product(&mut self, product: &FinancialProduct) -> io::Result<()> {
+        self.record.push(*product as u8);
+        Ok(())
+    }
+
+    /// same codes as `TryFrom<i8> for PutOrCall`
+    fn put_or_call(&mut self, put_or_call: &PutOrCall) -> io::Result<()> {
+        self.record.push(match put_or_call {
+            PutOrCall::Combo => 0,
+            PutOrCall::Call => 1,
+            PutOrCall::Put => 2,
+        });
+        Ok(())
+    }
+
+    /// interned string
+    fn symbol(&mut self, s: &str) -> io::Result<()> {
+        let id = match self.symbols.get(s) {
+            Some(id) => *id,
+            None => {
+                let id = self.symbols.len() as u32;
+                self.symbol_records.push(SYMBOL);
+                write_text(&mut self.symbol_records, s)?;
+                self.symbols.insert(s.to_string(), id);
+                id
+            }
+        };
+        self.record.extend_from_slice(&id.to_le_bytes());
+        Ok(())
+    }
+
+    /// string that is stored in the record
+    fn text(&mut self, s: &str) -> io::Result<()> {
+        write_text(&mut self.record, s)
+    }
+}
+
+fn write_text(buf: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    let len = u16::try_from(s.len()).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("{s:?} is too long"))
+    })?;
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+struct Decoder<R> {
+    reader: R,
+    symbols: Vec<String>,
+}
+
+impl<R: Read> Decoder<R> {
+    fn bytes<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn timestamp(&mut self) -> io::Result<NaiveDateTime> {
+        let ns = self.int()?;
+        let secs = ns.div_euclid(1_000_000_000);
+        let nsecs = ns.rem_euclid(1_000_000_000) as u32;
+        chrono::DateTime::from_timestamp(secs, nsecs)
+            .map(|dt| dt.naive_utc())
+            .ok_or_else(|| invalid_data(format!("invalid timestamp {ns}")))
+    }
+
+    fn int(&mut self) -> io::Result<i64> {
+        Ok(i64::from_le_bytes(self.bytes()?))
+    }
+
+    fn side(&mut self) -> io::Result<Side> {
+        let [c] = self.bytes()?;
+        Side::try_from(c as char).map_err(|_| invalid_data(format!("invalid side {c}")))
+    }
+
+    fn flag(&mut self) -> io::Result<bool> {
+        let [b] = self.bytes()?;
+        Ok(b != 0)
+    }
+
+    fn product(&mut self) -> io::Result<FinancialProduct> {
+        let [b] = self.bytes()?;
+        FinancialProduct::try_from(b as i8)
+            .map_err(|_| invalid_data(format!("invalid financial product {b}")))
+    }
+
+    fn put_or_call(&mut self) -> io::Result<PutOrCall> {
+        let [b] = self.bytes()?;
+        PutOrCall::try_from(b as i8).map_err(|_| invalid_data(format!("invalid put or call {b}")))
+    }
+
+    fn symbol(&mut self) -> io::Result<String> {
+        let id = u32::from_le_bytes(self.bytes()?);
+        self.symbols
+            .get(id as usize)
+            .cloned()
+            .ok_or_else(|| invalid_data(format!("unknown symbol {id}")))
+    }
+
+    fn text(&mut self) -> io::Result<String> {
+        let len = u16::from_le_bytes(self.bytes()?);
+        let mut buf = vec![0; len as usize];
+        self.reader.read_exact(&mut buf)?;
+        String::from_utf8(buf).map_err(|e| invalid_data(e.to_string()))
+    }
+}
+
+/// Message with a fixed binary layout
+trait Record: Sized {
+    fn encode(&self, enc: &mut Encoder) -> io::Result<()>;
+    fn decode<R: Read>(dec: &mut Decoder<R>) -> io::Result<Self>;
+}
+
+/// the fields are encoded in the listed order, the kind names the method of `Encoder` and `Decoder`
+macro_rules! impl_record {
+    ($($ty:ident { $($field:ident: $kind:ident,)* })*) => {
+        $(
+            impl Record for $ty {
+                fn encode(&self, enc: &mut Encoder) -> io::Result<()> {
+                    $( enc.$kind(&self.$field)?; )*
+                    Ok(())
+                }
+
+                fn decode<R: Read>(dec: &mut Decoder<R>) -> io::Result<Self> {
+                    Ok(Self {
+                        $( $field: dec.$kind()?, )*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_record! {
+    AddOrder {
+        timestamp: timestamp,
+        order_book_id: int,
+        order_id: int,
+        side: side,
+        order_book_position: int,
+        quantity: int,
+        price: int,
+    }
+    CombinationProduct {
+        timestamp: timestamp,
+        combination_order_book_id: int,
+        leg_order_book_id: int,
+        leg_side: side,
+        leg_ratio: int,
+    }
+    DeleteOrder {
+        timestamp: timestamp,
+        order_book_id: int,
+        order_id: int,
+        side: side,
+    }
+    EquilibriumPrice {
+        timestamp: timestamp,
+        order_book_id: int,
+        bid_qty_at_ep: int,
+        ask_qty_at_ep: int,
+        ep: int,
+    }
+    Executed {
+        timestamp: timestamp,
+        order_book_id: int,
+        order_id: int,
+        side: side,
+        executed_quantity: int,
+        match_id: text,
+        combo_group_id: int,
+    }
+    ExecutionWithPriceInfo {
+        timestamp: timestamp,
+        order_book_id: int,
+        order_id: int,
+        side: side,
+        executed_quantity: int,
+        match_id: int,
+        combo_group_id: int,
+        trade_price: int,
+        occurred_at_cross: flag,
+    }
+    LegPrice {
+        timestamp: timestamp,
+        order_book_id: int,
+        match_id: int,
+        combo_group_id: int,
+        quantity: int,
+        trade_price: int,
+        occurred_at_cross: flag,
+    }
+    ProductInfo {
+        timestamp: timestamp,
+        order_book_id: int,
+        symbol: symbol,
+        long_name: symbol,
+        financial_product: product,
+        number_of_decimal_in_price: int,
+        number_of_legs: int,
+        underlying_order_book_id: int,
+        strike_price: int,
+        expiration_date: int,
+        number_of_decimals_in_strike_price: int,
+        put_or_call: put_or_call,
+    }
+    SecondTag {
+        timestamp: timestamp,
+        second: int,
+    }
+    SystemEventInfo {
+        timestamp: timestamp,
+        event_code: symbol,
+    }
+    TickSize {
+        timestamp: timestamp,
+        order_book_id: int,
+        tick_size: int,
+        price_from: int,
+        price_to: int,
+    }
+    TradingStatusInfo {
+        timestamp: timestamp,
+        order_book_id: int,
+        state_name: symbol,
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::NaiveDateTime;
+use tom_orderbook::{
+    OrderBook,
+    OrderBookError,
+    UniqueOrderId,
+};
+
+use crate::{
+    AddOrder,
+    MessageEnum,
+    TickTable,
+    UniqueId,
+};
+
+/// How the builder places new orders in their price level
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PositionMode {
+    /// insert at `order_book_position`
+    #[default]
+    Exchange,
+    /// append in arrival order and report a `PositionDiscrepancy` when the order
+    /// does not end up at `order_book_position`. the order is then moved to the
+    /// exchange's position, so every discrepancy is reported once.
+    Validate,
+}
+
+/// New order whose rank in the reconstructed queue differs from the exchange's
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionDiscrepancy {
+    pub id: UniqueId,
+    pub timestamp: NaiveDateTime,
+    /// `order_book_position` of the `AddOrder`, starts at 1
+    pub expected: i64,
+    /// position the order got in the reconstructed queue, starts at 1
+    pub actual: i64,
+}
+
+/// What the builder does with new orders whose price is off the tick grid
+///
+/// the grid of an order book is known once its `TickSize` messages were applied,
+/// orders of books without tick sizes are never checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TickCheck {
+    #[default]
+    Off,
+    /// add the order and report an `OffTickOrder`
+    Flag,
+    /// refuse the order with `BookBuilderError::OffTick`
+    Reject,
+}
+
+/// New order whose price is not on the tick grid of its order book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffTickOrder {
+    pub id: UniqueId,
+    pub timestamp: NaiveDateTime,
+    pub price: i64,
+}
+
+/// Error returned by `OsakaBookBuilder::apply`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BookBuilderError {
+    Book(OrderBookError),
+    /// the price of the new order is not on the tick grid of its order book, see `TickCheck::Reject`
+    OffTick {
+        id: UniqueId,
+        price: i64,
+    },
+}
+
+impl From<OrderBookError> for BookBuilderError {
+    fn from(e: OrderBookError) -> Self {
+        Self::Book(e)
+    }
+}
+
+impl fmt::Display for BookBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Book(e) => write!(f, "{e}"),
+            Self::OffTick { id, price } => write!(
+                f,
+                "price {price} of order {} in order book {} is not on the tick grid",
+                id.order_id, id.order_book_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BookBuilderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Book(e) => Some(e),
+            Self::OffTick { .. } => None,
+        }
+    }
+}
+
+/// Reconstructs the order books of J-GATE from a stream of messages
+///
+/// keeps one `OrderBook` per `order_book_id` that stores the original `AddOrder`.
+/// orders are keyed by `UniqueId` and new orders are placed according to the `PositionMode`.
+#[derive(Default)]
+pub struct OsakaBookBuilder {
+    books: HashMap<i64, OrderBook<AddOrder>>,
+    position_mode: PositionMode,
+    discrepancies: Vec<PositionDiscrepancy>,
+    tick_tables: HashMap<i64, TickTable>,
+    tick_check: TickCheck,
+    off_tick_orders: Vec<OffTickOrder>,
+}
+
+impl OsakaBookBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_position_mode(position_mode: PositionMode) -> Self {
+        Self {
+            position_mode,
+            ..Self::default()
+        }
+    }
+
+    pub fn position_mode(&self) -> PositionMode {
+        self.position_mode
+    }
+
+    pub fn set_tick_check(&mut self, tick_check: TickCheck) {
+        self.tick_check = tick_check;
+    }
+
+    pub fn tick_check(&self) -> TickCheck {
+        self.tick_check
+    }
+
+    /// tick sizes of the order book, `None` if no `TickSize` was applied for it
+    pub fn tick_table(&self, order_book_id: i64) -> Option<&TickTable> {
+        self.tick_tables.get(&order_book_id)
+    }
+
+    /// orders found since the last `take_off_tick_orders`, only filled in `TickCheck::Flag`
+    pub fn off_tick_orders(&self) -> &[OffTickOrder] {
+        &self.off_tick_orders
+    }
+
+    pub fn take_off_tick_orders(&mut self) -> Vec<OffTickOrder> {
+        std::mem::take(&mut self.off_tick_orders)
+    }
+
+    /// discrepancies found since the last `take_discrepancies`, always empty in `PositionMode::Exchange`
+    pub fn discrepancies(&self) -> &[PositionDiscrepancy] {
+        &self.discrepancies
+    }
+
+    pub fn take_discrepancies(&mut self) -> Vec<PositionDiscrepancy> {
+        std::mem::take(&mut self.discrepancies)
+    }
+
+    /// applies `AddOrder`, `DeleteOrder`, `Executed` and `ExecutionWithPriceInfo`,
+    /// `TickSize` is kept for the `TickCheck` and other messages are ignored.
+    /// returns true if the message was applied to a book.
+    pub fn apply(&mut self, msg: &MessageEnum) -> Result<bool, BookBuilderError> {
+        match msg {
+            MessageEnum::TickSize(tick) => {
+                self.tick_tables
+                    .entry(tick.order_book_id)
+                    .or_default()
+                    .insert(*tick.clone());
+                return Ok(false);
+            }
+            MessageEnum::AddOrder(add) => {
+                self.check_tick(add)?;
+                let book = self
+                    .books
+                    .entry(add.order_book_id)
+                    .or_insert_with(|| OrderBook::new(add.order_book_id as u64));
+                // order_book_position starts at 1
+                let rank = (add.order_book_position - 1).max(0) as usize;
+                match self.position_mode {
+                    PositionMode::Exchange => {
+                        book.add_at(**add, rank)?;
+                    }
+                    PositionMode::Validate => {
+                        let id = UniqueId::from_add_order(add);
+                        book.add(**add)?;
+                        let (ahead, _) = book.queue_position(id.unique_order_id())?;
+                        if ahead != rank {
+                            self.discrepancies.push(PositionDiscrepancy {
+                                id,
+                                timestamp: add.timestamp,
+                                expected: add.order_book_position,
+                                actual: ahead as i64 + 1,
+                            });
+                            let order = book.remove(id)?;
+                            book.add_at(order, rank)?;
+                        }
+                    }
+                }
+            }
+            MessageEnum::DeleteOrder(del) => {
+                let id = UniqueId::from_delete_order(del);
+                self.book_of(&id)?.remove(id)?;
+            }
+            MessageEnum::Executed(exec) => {
+                let id = UniqueId {
+                    order_book_id: exec.order_book_id,
+                    order_id: exec.order_id,
+                    side: exec.side,
+                };
+                self.book_of(&id)?.execute(exec.as_ref())?;
+            }
+            MessageEnum::ExecutionWithPriceInfo(exec) => {
+                let id = UniqueId {
+                    order_book_id: exec.order_book_id,
+                    order_id: exec.order_id,
+                    side: exec.side,
+                };
+                self.book_of(&id)?.execute(exec.as_ref())?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn book(&self, order_book_id: i64) -> Option<&OrderBook<AddOrder>> {
+        self.books.get(&order_book_id)
+    }
+
+    /// iterates every book in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (i64, &OrderBook<AddOrder>)> {
+        self.books.iter().map(|(id, book)| (*id, book))
+    }
+
+    fn check_tick(&mut self, add: &AddOrder) -> Result<(), BookBuilderError> {
+        if self.tick_check == TickCheck::Off {
+            return Ok(());
+        }
+        let on_tick = self
+            .tick_tables
+            .get(&add.order_book_id)
+            .is_none_or(|table| table.is_on_tick(add.price));
+        if on_tick {
+            return Ok(());
+        }
+        let id = UniqueId::from_add_order(add);
+        match self.tick_check {
+            TickCheck::Reject => Err(BookBuilderError::OffTick {
+                id,
+                price: add.price,
+            }),
+            _ => {
+                self.off_tick_orders.push(OffTickOrder {
+                    id,
+                    timestamp: add.timestamp,
+                    price: add.price,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn book_of(&mut self, id: &UniqueId) -> Result<&mut OrderBook<AddOrder>, OrderBookError> {
+        self.books
+            .get_mut(&id.order_book_id)
+            .ok_or(OrderBookError::UnknownOrderId(id.unique_order_id()))
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use market_datatypes::{
+    FixedPrice,
+    PriceScale,
+};
+
+use crate::{
+    CombinationProduct,
+    FinancialProduct,
+    MessageEnum,
+    ProductInfo,
+    TickSize,
+    TickTable,
+};
+
+/// Reference data of the instruments, built from `ProductInfo`, `TickSize` and `CombinationProduct`
+///
+/// a message for an instrument that is already known replaces the old data,
+/// so the catalog can be fed every day of a feed.
+#[derive(Default)]
+pub struct InstrumentCatalog {
+    products: HashMap<i64, ProductInfo>,
+    tick_tables: HashMap<i64, TickTable>,
+    /// legs of every combination order book
+    legs: HashMap<i64, Vec<CombinationProduct>>,
+    /// (underlying_order_book_id, expiration_date) -> order_book_id of the options
+    chains: HashMap<(i64, i64), Vec<i64>>,
+}
+
+impl InstrumentCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ingests `ProductInfo`, `TickSize` and `CombinationProduct`, other messages are ignored.
+    /// returns true if the message was used.
+    pub fn apply(&mut self, msg: &MessageEnum) -> bool {
+        match msg {
+            MessageEnum::ProductInfo(info) => self.add_product(*info.clone()),
+            MessageEnum::TickSize(tick) => self.add_tick_size(*tick.clone()),
+            MessageEnum::CombinationProduct(leg) => self.add_leg(*leg.clone()),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn add_product(&mut self, info: ProductInfo) {
+        if let Some(old) = self.products.get(&info.order_book_id) {
+            let key = (old.underlying_order_book_id, old.expiration_date);
+            if let Some(chain) = self.chains.get_mut(&key) {
+                chain.retain(|id| *id != info.order_book_id);
+            }
+        }
+        if info.financial_product == FinancialProduct::Option {
+            self.chains
+                .entry((info.underlying_order_book_id, info.expiration_date))
+                .or_default()
+                .push(info.order_book_id);
+        }
+        self.products.insert(info.order_book_id, info);
+    }
+
+    /// a tick size with the same `price_from` is replaced
+    pub fn add_tick_size(&mut self, tick: TickSize) {
+        self.tick_tables
+            .entry(tick.order_book_id)
+            .or_default()
+            .insert(tick);
+    }
+
+    /// a leg with the same `leg_order_book_id` is replaced
+    pub fn add_leg(&mut self, leg: CombinationProduct) {
+        let legs = self.legs.entry(leg.combination_order_book_id).or_default();
+        match legs
+            .iter_mut()
+            .find(|l| l.leg_order_book_id == leg.leg_order_book_id)
+        {
+            Some(old) => *old = leg,
+            None => legs.push(leg),
+        }
+    }
+
+    pub fn product(&self, order_book_id: i64) -> Option<&ProductInfo> {
+        self.products.get(&order_book_id)
+    }
+
+    pub fn symbol(&self, order_book_id: i64) -> Option<&str> {
+        self.product(order_book_id).map(|info| info.symbol.as_str())
+    }
+
+    /// number of decimals of the prices of the order book
+    pub fn price_decimals(&self, order_book_id: i64) -> Option<i64> {
+        self.product(order_book_id)
+            .map(|info| info.number_of_decimal_in_price)
+    }
+
+    pub fn price_scale(&self, order_book_id: i64) -> Option<PriceScale> {
+        self.product(order_book_id)?.price_scale()
+    }
+
+    /// integer price of the order book with its scale attached
+    pub fn fixed_price(&self, order_book_id: i64, price: i64) -> Option<FixedPrice> {
+        Some(self.price_scale(order_book_id)?.price(price))
+    }
+
+    /// tick size of the range that contains the price, both ends of a range are inclusive
+    pub fn tick_size_at(&self, order_book_id: i64, price: i64) -> Option<i64> {
+        self.tick_table(order_book_id)?.tick_at(price)
+    }
+
+    /// tick sizes of the order book, `None` if no `TickSize` was seen for it
+    pub fn tick_table(&self, order_book_id: i64) -> Option<&TickTable> {
+        self.tick_tables.get(&order_book_id)
+    }
+
+    /// legs of the combination, empty if the order book is not a known combination
+    pub fn legs(&self, combo_id: i64) -> &[CombinationProduct] {
+        self.legs.get(&combo_id).map_or(&[], |legs| legs.as_slice())
+    }
+
+    /// options on the underlying that expire on `expiry` (yyyymmdd),
+    /// sorted by strike price, calls before puts
+    pub fn options_chain(&self, underlying: i64, expiry: i64) -> Vec<&ProductInfo> {
+        let mut chain: Vec<_> = self
+            .chains
+            .get(&(underlying, expiry))
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.products.get(id))
+            .collect();
+        chain.sort_by_key(|info| (info.strike_price, info.put_or_call.is_put()));
+        chain
+    }
+
+    /// number of instruments
+    pub fn len(&self) -> usize {
+        self.products.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.products.is_empty()
+    }
+
+    /// iterates every instrument in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &ProductInfo> {
+        self.products.values()
+    }
+}
@@ -1,19 +1,17 @@
 // automatically generated
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use super::util::{
-    extract_datetime,
-    extract_value,
-};
+use crate::util::Fields;
 use crate::{
     tag_guard,
+    FieldError,
     Side,
+    UniqueId,
 };
+use market_datatypes::OrderPrice;
 
 ///
 ///6.4.1 新規注文タグ （タグ ID ： A ）
@@ -52,21 +50,19 @@ impl_message! {
 }
 
 impl TryFrom<&str> for AddOrder {
-    type Error = ();
+    type Error = FieldError;
 
     //(s: &str, row_no: i64, filename: i64)
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('A', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let order_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let order_book_id = FromStr::from_str(extract_value(iter.next().ok_or(())?).ok_or(())?)
-            .ok()
-            .ok_or(())?;
-        let side = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let order_book_position = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let quantity = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let price = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
+        let order_id = iter.parse("order_id")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
+        let side = iter.parse("side")?;
+        let order_book_position = iter.parse("order_book_position")?;
+        let quantity = iter.parse("quantity")?;
+        let price = iter.parse("price")?;
         Ok(Self {
             timestamp,
             order_book_id,
@@ -78,3 +74,31 @@ impl TryFrom<&str> for AddOrder {
         })
     }
 }
+
+impl tom_orderbook::UniqueOrderId for AddOrder {
+    fn unique_order_id(&self) -> u64 {
+        UniqueId::from_add_order(self).unique_order_id()
+    }
+}
+
+/// the book keeps the original message, `quantity` is updated on executions
+impl tom_orderbook::Order for AddOrder {
+    type Price = i64;
+    type Qty = i64;
+
+    fn price(&self) -> OrderPrice<i64> {
+        OrderPrice::Limit(self.price)
+    }
+
+    fn qty(&self) -> i64 {
+        self.quantity
+    }
+
+    fn side(&self) -> market_datatypes::Side {
+        self.side.into()
+    }
+
+    fn set_qty(&mut self, qty: i64) {
+        self.quantity = qty;
+    }
+}
@@ -1,19 +1,17 @@
 // automatically generated
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::util::{
-    extract_datetime,
-    extract_value_and_parse,
-};
+use crate::util::Fields;
 use crate::{
     tag_guard,
+    FieldError,
     Side,
+    UniqueId,
 };
+use tom_orderbook::UniqueOrderId;
 
 ///
 /// 6.4.3 価格情報付約定通知タグ （タグ ID ： C ）
@@ -73,33 +71,33 @@ impl_message! {
 }
 
 impl TryFrom<&str> for ExecutionWithPriceInfo {
-    type Error = ();
+    type Error = FieldError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('C', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let order_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let order_book_id = extract_value_and_parse(iter.next().ok_or(())?).ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
+        let order_id = iter.parse("order_id")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
 
-        let side = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let side = iter.parse("side")?;
 
-        let executed_quantity = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let match_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let combo_group_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let executed_quantity = iter.parse("executed_quantity")?;
+        let match_id = iter.parse("match_id")?;
+        let combo_group_id = iter.parse("combo_group_id")?;
 
         let _reserved = iter.next();
         let _reserved = iter.next();
 
-        let trade_price = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let occurred_at_cross: String = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let trade_price = iter.parse("trade_price")?;
+        let occurred_at_cross = iter.field("occurred_at_cross")? == "Y";
 
         Ok(Self {
             timestamp,
             combo_group_id,
             executed_quantity,
             match_id,
-            occurred_at_cross: occurred_at_cross.as_str() == "Y",
+            occurred_at_cross,
             order_book_id,
             order_id,
             side,
@@ -107,3 +105,23 @@ impl TryFrom<&str> for ExecutionWithPriceInfo {
         })
     }
 }
+
+impl tom_orderbook::Execution for ExecutionWithPriceInfo {
+    fn maker_id(&self) -> u64 {
+        UniqueId {
+            order_book_id: self.order_book_id,
+            order_id: self.order_id,
+            side: self.side,
+        }
+        .unique_order_id()
+    }
+
+    fn size(&self) -> i64 {
+        self.executed_quantity
+    }
+
+    /// the execution can happen at a different price than the one of the resting order
+    fn price(&self) -> Option<i64> {
+        Some(self.trade_price)
+    }
+}
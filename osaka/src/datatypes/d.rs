@@ -1,17 +1,13 @@
 // automatically generated
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::util::{
-    extract_datetime,
-    extract_value_and_parse,
-};
+use crate::util::Fields;
 use crate::{
     tag_guard,
+    FieldError,
     Side,
 };
 
@@ -44,18 +40,18 @@ impl_message! {
 }
 
 impl TryFrom<&str> for DeleteOrder {
-    type Error = ();
+    type Error = FieldError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('D', s);
-        let mut iter = s.split(",").skip(1);
+        let mut iter = Fields::new(s);
 
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
+        let timestamp = iter.datetime("timestamp")?;
 
-        let order_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let order_book_id = extract_value_and_parse(iter.next().ok_or(())?).ok_or(())?;
+        let order_id = iter.parse("order_id")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
 
-        let side = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let side = iter.parse("side")?;
         Ok(Self {
             timestamp,
             order_book_id,
@@ -1,19 +1,17 @@
 // automatically generated
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::util::{
-    extract_datetime,
-    extract_value_and_parse,
-};
+use crate::util::Fields;
 use crate::{
     tag_guard,
+    FieldError,
     Side,
+    UniqueId,
 };
+use tom_orderbook::UniqueOrderId;
 
 ///
 ///6.4.2 約定通知タグ （タグ ID ： E ）
@@ -51,22 +49,34 @@ impl_message! {
     pub side: Side,
 }
 
-impl TryFrom<&str> for Executed {
-    type Error = ();
+/// `Executed` that borrows its strings from the line
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ExecutedRef<'a> {
+    pub timestamp: NaiveDateTime,
+    pub combo_group_id: i64,
+    pub executed_quantity: i64,
+    pub match_id: &'a str,
+    pub order_book_id: i64,
+    pub order_id: i64,
+    pub side: Side,
+}
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
+impl<'a> TryFrom<&'a str> for ExecutedRef<'a> {
+    type Error = FieldError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
         tag_guard!('E', s);
-        let mut iter = s.split(",").skip(1);
+        let mut iter = Fields::new(s);
 
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let order_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let order_book_id = extract_value_and_parse(iter.next().ok_or(())?).ok_or(())?;
-        let side = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let timestamp = iter.datetime("timestamp")?;
+        let order_id = iter.parse("order_id")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
+        let side = iter.parse("side")?;
 
-        let executed_quantity = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let match_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let executed_quantity = iter.parse("executed_quantity")?;
+        let match_id = iter.field("match_id")?;
 
-        let combo_group_id = extract_value_and_parse(iter.next().ok_or(())?).ok_or(())?;
+        let combo_group_id = iter.parse_value("combo_group_id")?;
 
         Ok(Self {
             timestamp,
@@ -79,3 +89,40 @@ impl TryFrom<&str> for Executed {
         })
     }
 }
+
+impl From<ExecutedRef<'_>> for Executed {
+    fn from(r: ExecutedRef<'_>) -> Self {
+        Self {
+            timestamp: r.timestamp,
+            combo_group_id: r.combo_group_id,
+            executed_quantity: r.executed_quantity,
+            match_id: r.match_id.to_string(),
+            order_book_id: r.order_book_id,
+            order_id: r.order_id,
+            side: r.side,
+        }
+    }
+}
+
+impl TryFrom<&str> for Executed {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        ExecutedRef::try_from(s).map(Self::from)
+    }
+}
+
+impl tom_orderbook::Execution for Executed {
+    fn maker_id(&self) -> u64 {
+        UniqueId {
+            order_book_id: self.order_book_id,
+            order_id: self.order_id,
+            side: self.side,
+        }
+        .unique_order_id()
+    }
+
+    fn size(&self) -> i64 {
+        self.executed_quantity
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+/// Field of a line that could not be parsed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldError {
+    pub field: &'static str,
+    /// the raw value, `None` if the line ended before the field
+    pub value: Option<String>,
+}
+
+impl FieldError {
+    pub fn missing(field: &'static str) -> Self {
+        Self { field, value: None }
+    }
+
+    pub fn invalid(field: &'static str, value: &str) -> Self {
+        Self {
+            field,
+            value: Some(value.to_string()),
+        }
+    }
+
+    /// the line starts with another tag
+    pub fn tag(tag: Option<char>) -> Self {
+        Self {
+            field: "tag",
+            value: tag.map(String::from),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "invalid {}: {:?}", self.field, value),
+            None => write!(f, "missing {}", self.field),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Error of reading one line of a tag file
+#[derive(Debug)]
+pub struct ParseError {
+    /// line number in the file, starts at 1
+    pub line: usize,
+    /// first character of the line
+    pub tag: Option<char>,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    /// the first character of the line is not a known tag
+    UnknownTag,
+    Field(FieldError),
+    Io(std::io::Error),
+}
+
+impl ParseError {
+    /// name of the field that could not be parsed
+    pub fn field(&self) -> Option<&'static str> {
+        match &self.kind {
+            ParseErrorKind::Field(e) => Some(e.field),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}", self.line)?;
+        if let Some(tag) = self.tag {
+            write!(f, " (tag {tag})")?;
+        }
+        match &self.kind {
+            ParseErrorKind::UnknownTag => write!(f, ": unknown tag"),
+            ParseErrorKind::Field(e) => write!(f, ": {e}"),
+            ParseErrorKind::Io(e) => write!(f, ": {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Field(e) => Some(e),
+            ParseErrorKind::Io(e) => Some(e),
+            ParseErrorKind::UnknownTag => None,
+        }
+    }
+}
@@ -1,16 +1,14 @@
 // automatically generated
 
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::tag_guard;
-use crate::util::{
-    extract_datetime,
-    extract_value_and_parse,
+use crate::util::Fields;
+use crate::{
+    tag_guard,
+    FieldError,
 };
 
 ///
@@ -42,16 +40,16 @@ impl_message! {
 }
 
 impl TryFrom<&str> for TickSize {
-    type Error = ();
+    type Error = FieldError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('L', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let order_book_id = extract_value_and_parse(iter.next().ok_or(())?).ok_or(())?;
-        let tick_size = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let price_from = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let price_to = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
+        let tick_size = iter.parse("tick_size")?;
+        let price_from = iter.parse("price_from")?;
+        let price_to = iter.parse("price_to")?;
         Ok(Self {
             timestamp,
             order_book_id,
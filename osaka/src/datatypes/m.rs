@@ -1,14 +1,13 @@
 // automatically generated
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::util::extract_datetime;
+use crate::util::Fields;
 use crate::{
     tag_guard,
+    FieldError,
     Side,
 };
 
@@ -50,16 +49,16 @@ impl_message! {
 }
 
 impl TryFrom<&str> for CombinationProduct {
-    type Error = ();
+    type Error = FieldError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('M', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let combination_order_book_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let leg_order_book_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let leg_side = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let leg_ratio = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
+        let combination_order_book_id = iter.parse("combination_order_book_id")?;
+        let leg_order_book_id = iter.parse("leg_order_book_id")?;
+        let leg_side = iter.parse("leg_side")?;
+        let leg_ratio = iter.parse("leg_ratio")?;
         Ok(Self {
             timestamp,
             combination_order_book_id,
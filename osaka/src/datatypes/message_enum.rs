@@ -15,6 +15,8 @@ use crate::{
     Executed,
     ExecutionWithPriceInfo,
     LegPrice,
+    MessageRef,
+    ParseError,
     ProductInfo,
     SecondTag,
     SystemEventInfo,
@@ -56,27 +58,28 @@ macro_rules! dclr_message_enum {
             }
         ) *
 
+        impl MessageEnum {
+            /// parses the line with the parser of its leading tag.
+            /// `line` is only used for the error.
+            pub fn from_line(s: &str, line: usize) -> Result<Self, ParseError> {
+                MessageRef::from_line(s, line).map(MessageRef::into_owned)
+            }
+        }
+
         impl TryFrom<String> for MessageEnum {
             type Error = String;
             fn try_from(string: String) -> Result<Self, Self::Error> {
-                $(
-                    if let Ok(i) = $ident::try_from(string.as_str()) {
-                        return Ok(MessageEnum::$ident(Box::new(i)))
-                    }
-                ) *
-                return Err(string)
+                match MessageEnum::from_line(&string, 0) {
+                    Ok(i) => Ok(i),
+                    Err(_) => Err(string),
+                }
             }
         }
 
         impl FromStr for MessageEnum {
             type Err = String;
             fn from_str(string: &str) -> Result<Self, Self::Err> {
-                $(
-                    if let Ok(i) = $ident::try_from(string) {
-                        return Ok(MessageEnum::$ident(Box::new(i)))
-                    }
-                ) *
-                return Err(string.to_string())
+                MessageEnum::from_line(string, 0).map_err(|_| string.to_string())
             }
         }
 
@@ -0,0 +1,100 @@
+use chrono::NaiveDateTime;
+
+use crate::{
+    AddOrder,
+    CombinationProduct,
+    DeleteOrder,
+    EquilibriumPrice,
+    Executed,
+    ExecutedRef,
+    ExecutionWithPriceInfo,
+    LegPrice,
+    MessageEnum,
+    ParseError,
+    ParseErrorKind,
+    ProductInfo,
+    ProductInfoRef,
+    SecondTag,
+    SystemEventInfo,
+    SystemEventInfoRef,
+    TickSize,
+    TradingStatusInfo,
+    TradingStatusInfoRef,
+};
+
+macro_rules! dclr_message_ref {
+    ($($ident:ident => $ty:ty,)*) => {
+        /// Message that borrows from its line
+        ///
+        /// parsing does not allocate, strings are kept as `&'a str`.
+        /// `into_owned` converts it to a `MessageEnum`.
+        #[derive(Debug, PartialEq, Eq, Clone)]
+        pub enum MessageRef<'a> {
+            $( $ident($ty), )*
+        }
+
+        impl<'a> MessageRef<'a> {
+            pub fn tag(&self) -> char {
+                match self {
+                    $( MessageRef::$ident(_) => $ident::TAG, )*
+                }
+            }
+
+            pub fn timestamp(&self) -> NaiveDateTime {
+                match self {
+                    $( MessageRef::$ident(x) => x.timestamp, )*
+                }
+            }
+
+            /// parses the line with the parser of its leading tag.
+            /// `line` is only used for the error.
+            pub fn from_line(s: &'a str, line: usize) -> Result<Self, ParseError> {
+                let tag = s.chars().next();
+                let result = match tag {
+                    $(
+                        Some($ident::TAG) => <$ty>::try_from(s).map(MessageRef::$ident),
+                    ) *
+                    _ => {
+                        return Err(ParseError {
+                            line,
+                            tag,
+                            kind: ParseErrorKind::UnknownTag,
+                        })
+                    }
+                };
+                result.map_err(|e| ParseError {
+                    line,
+                    tag,
+                    kind: ParseErrorKind::Field(e),
+                })
+            }
+
+            pub fn into_owned(self) -> MessageEnum {
+                match self {
+                    $( MessageRef::$ident(x) => MessageEnum::$ident(Box::new(x.into())), )*
+                }
+            }
+        }
+
+        impl From<MessageRef<'_>> for MessageEnum {
+            fn from(msg: MessageRef<'_>) -> Self {
+                msg.into_owned()
+            }
+        }
+    };
+}
+
+dclr_message_ref!(
+    CombinationProduct => CombinationProduct,
+    DeleteOrder => DeleteOrder,
+    EquilibriumPrice => EquilibriumPrice,
+    Executed => ExecutedRef<'a>,
+    ExecutionWithPriceInfo => ExecutionWithPriceInfo,
+    LegPrice => LegPrice,
+    ProductInfo => ProductInfoRef<'a>,
+    AddOrder => AddOrder,
+    SecondTag => SecondTag,
+    SystemEventInfo => SystemEventInfoRef<'a>,
+    TickSize => TickSize,
+    TradingStatusInfo => TradingStatusInfoRef<'a>,
+);
@@ -17,6 +17,13 @@ macro_rules! impl_message {
 }
 
 
+mod error;
+pub use error::{
+    FieldError,
+    ParseError,
+    ParseErrorKind,
+};
+
 mod unique_id;
 pub use unique_id::UniqueId;
 
@@ -27,19 +34,31 @@ pub use c::ExecutionWithPriceInfo;
 mod d;
 pub use d::DeleteOrder;
 mod e;
-pub use e::Executed;
+pub use e::{
+    Executed,
+    ExecutedRef,
+};
 mod l;
 pub use l::TickSize;
 mod m;
 pub use m::CombinationProduct;
 mod o;
-pub use o::TradingStatusInfo;
+pub use o::{
+    TradingStatusInfo,
+    TradingStatusInfoRef,
+};
 mod p;
 pub use p::LegPrice;
 mod r;
-pub use r::ProductInfo;
+pub use r::{
+    ProductInfo,
+    ProductInfoRef,
+};
 mod s;
-pub use s::SystemEventInfo;
+pub use s::{
+    SystemEventInfo,
+    SystemEventInfoRef,
+};
 mod t;
 pub use t::SecondTag;
 mod z;
@@ -48,6 +67,9 @@ pub use z::EquilibriumPrice;
 mod message_enum;
 pub use message_enum::MessageEnum;
 
+mod message_ref;
+pub use message_ref::MessageRef;
+
 mod financial_product;
 pub use financial_product::FinancialProduct;
 
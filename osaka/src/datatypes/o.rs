@@ -1,16 +1,14 @@
 // automatically generated
 
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::tag_guard;
-use crate::util::{
-    extract_datetime,
-    extract_value_and_parse,
+use crate::util::Fields;
+use crate::{
+    tag_guard,
+    FieldError,
 };
 
 ///
@@ -39,15 +37,23 @@ impl_message! {
     pub state_name: String,
 }
 
-impl TryFrom<&str> for TradingStatusInfo {
-    type Error = ();
+/// `TradingStatusInfo` that borrows its strings from the line
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TradingStatusInfoRef<'a> {
+    pub timestamp: NaiveDateTime,
+    pub order_book_id: i64,
+    pub state_name: &'a str,
+}
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
+impl<'a> TryFrom<&'a str> for TradingStatusInfoRef<'a> {
+    type Error = FieldError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
         tag_guard!('O', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let order_book_id = extract_value_and_parse(iter.next().ok_or(())?).ok_or(())?;
-        let state_name = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
+        let state_name = iter.field("state_name")?;
         Ok(Self {
             timestamp,
             order_book_id,
@@ -55,3 +61,21 @@ impl TryFrom<&str> for TradingStatusInfo {
         })
     }
 }
+
+impl From<TradingStatusInfoRef<'_>> for TradingStatusInfo {
+    fn from(r: TradingStatusInfoRef<'_>) -> Self {
+        Self {
+            timestamp: r.timestamp,
+            order_book_id: r.order_book_id,
+            state_name: r.state_name.to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for TradingStatusInfo {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        TradingStatusInfoRef::try_from(s).map(Self::from)
+    }
+}
@@ -1,15 +1,13 @@
 // automatically generated
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::tag_guard;
-use crate::util::{
-    extract_datetime,
-    extract_value_and_parse,
+use crate::util::Fields;
+use crate::{
+    tag_guard,
+    FieldError,
 };
 
 ///
@@ -51,23 +49,22 @@ impl_message! {
 }
 
 impl TryFrom<&str> for LegPrice {
-    type Error = ();
+    type Error = FieldError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('P', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).unwrap();
-        let match_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let combo_group_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
+        let match_id = iter.parse("match_id")?;
+        let combo_group_id = iter.parse("combo_group_id")?;
         let _side = iter.next();
-        let quantity = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let order_book_id = extract_value_and_parse(iter.next().ok_or(())?).unwrap();
-        let trade_price = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let quantity = iter.parse("quantity")?;
+        let order_book_id = iter.parse_value("order_book_id")?;
+        let trade_price = iter.parse("trade_price")?;
         iter.next();
         iter.next();
         iter.next();
-        let _occurred_at_cross: String = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let occurred_at_cross = _occurred_at_cross.as_str() == "Y";
+        let occurred_at_cross = iter.field("occurred_at_cross")? == "Y";
 
         Ok(Self {
             timestamp,
@@ -1,15 +1,19 @@
 // automatically generated
 
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::util::extract_datetime;
+use market_datatypes::{
+    FixedPrice,
+    PriceScale,
+};
+
+use crate::util::Fields;
 use crate::{
     tag_guard,
+    FieldError,
     FinancialProduct,
     PutOrCall,
 };
@@ -60,26 +64,56 @@ impl_message! {
     pub underlying_order_book_id: i64,
 }
 
-impl TryFrom<&str> for ProductInfo {
-    type Error = ();
+impl ProductInfo {
+    /// scale of the prices of the order book, `None` if the feed sent an unsupported number of decimals
+    pub fn price_scale(&self) -> Option<PriceScale> {
+        PriceScale::try_new(self.number_of_decimal_in_price)
+    }
+
+    /// strike price with its own number of decimals
+    pub fn strike(&self) -> Option<FixedPrice> {
+        PriceScale::try_new(self.number_of_decimals_in_strike_price)
+            .map(|scale| scale.price(self.strike_price))
+    }
+}
+
+/// `ProductInfo` that borrows its strings from the line
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ProductInfoRef<'a> {
+    pub timestamp: NaiveDateTime,
+    pub expiration_date: i64,
+    pub financial_product: FinancialProduct,
+    pub long_name: &'a str,
+    pub number_of_decimal_in_price: i64,
+    pub number_of_decimals_in_strike_price: i64,
+    pub number_of_legs: i64,
+    pub order_book_id: i64,
+    pub put_or_call: PutOrCall,
+    pub strike_price: i64,
+    pub symbol: &'a str,
+    pub underlying_order_book_id: i64,
+}
+
+impl<'a> TryFrom<&'a str> for ProductInfoRef<'a> {
+    type Error = FieldError;
 
     //parse_row!(@ parse_r, ["_","timestamp","order_book_id","symbol","long_name","_reserved","financial_product","_trading_currency","number_of_decimal_in_price","_nominal_value",
     // "_odd_lot_size","_round_lot_size","_block_lot_size","_nominal_value","number_of_legs","underlying_order_book_id","strike_price","expiration_date","number_of_decimals_in_strike_price","put_or_call"]);
     //R,2021-03-30T21:14:49.816929242(1617138889816929242),590334,FUT_NK225M_2109,166090019,166090019,3,JPY,4,0,0,1,0,0,0,510,0,20210910,0,0
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
         tag_guard!('R', s);
-        let mut iter = s.split(",").skip(1);
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
+        let mut iter = Fields::new(s);
+        let timestamp = iter.datetime("timestamp")?;
 
-        let order_book_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let symbol = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let order_book_id = iter.parse("order_book_id")?;
+        let symbol = iter.field("symbol")?;
 
-        let long_name = iter.next().ok_or(())?.to_string();
+        let long_name = iter.field("long_name")?;
         let _reserved = iter.next();
-        let financial_product = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let financial_product = iter.parse("financial_product")?;
         let _trading_currency = iter.next();
-        let number_of_decimal_in_price = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let number_of_decimal_in_price = iter.parse("number_of_decimal_in_price")?;
 
         // iter.next
         let _nominal_value = iter.next();
@@ -88,14 +122,13 @@ impl TryFrom<&str> for ProductInfo {
         let _block_lot_size = iter.next();
         let _nominal_value = iter.next();
 
-        let number_of_legs = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let underlying_order_book_id = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let strike_price = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let expiration_date = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let number_of_decimals_in_strike_price =
-            FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let number_of_legs = iter.parse("number_of_legs")?;
+        let underlying_order_book_id = iter.parse("underlying_order_book_id")?;
+        let strike_price = iter.parse("strike_price")?;
+        let expiration_date = iter.parse("expiration_date")?;
+        let number_of_decimals_in_strike_price = iter.parse("number_of_decimals_in_strike_price")?;
 
-        let put_or_call = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let put_or_call = iter.parse("put_or_call")?;
 
         Ok(Self {
             timestamp,
@@ -113,3 +146,30 @@ impl TryFrom<&str> for ProductInfo {
         })
     }
 }
+
+impl From<ProductInfoRef<'_>> for ProductInfo {
+    fn from(r: ProductInfoRef<'_>) -> Self {
+        Self {
+            timestamp: r.timestamp,
+            expiration_date: r.expiration_date,
+            financial_product: r.financial_product,
+            long_name: r.long_name.to_string(),
+            number_of_decimal_in_price: r.number_of_decimal_in_price,
+            number_of_decimals_in_strike_price: r.number_of_decimals_in_strike_price,
+            number_of_legs: r.number_of_legs,
+            order_book_id: r.order_book_id,
+            put_or_call: r.put_or_call,
+            strike_price: r.strike_price,
+            symbol: r.symbol.to_string(),
+            underlying_order_book_id: r.underlying_order_book_id,
+        }
+    }
+}
+
+impl TryFrom<&str> for ProductInfo {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        ProductInfoRef::try_from(s).map(Self::from)
+    }
+}
@@ -1,14 +1,15 @@
 // automatically generated
 
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::tag_guard;
-use crate::util::extract_datetime;
+use crate::util::Fields;
+use crate::{
+    tag_guard,
+    FieldError,
+};
 
 ///
 ///6.3.5 システムイベント情報タグ （タグ ID ： S ）
@@ -39,18 +40,42 @@ impl_message! {
     pub event_code: String,
 }
 
-impl TryFrom<&str> for SystemEventInfo {
-    type Error = ();
+/// `SystemEventInfo` that borrows its strings from the line
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SystemEventInfoRef<'a> {
+    pub timestamp: NaiveDateTime,
+    pub event_code: &'a str,
+}
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
+impl<'a> TryFrom<&'a str> for SystemEventInfoRef<'a> {
+    type Error = FieldError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
         tag_guard!('S', s);
-        let mut iter = s.split(",").skip(1);
+        let mut iter = Fields::new(s);
 
-        let timestamp = extract_datetime(iter.next().ok_or(())?).ok_or(())?;
-        let event_code = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
+        let timestamp = iter.datetime("timestamp")?;
+        let event_code = iter.field("event_code")?;
         Ok(Self {
             timestamp,
             event_code,
         })
     }
 }
+
+impl From<SystemEventInfoRef<'_>> for SystemEventInfo {
+    fn from(r: SystemEventInfoRef<'_>) -> Self {
+        Self {
+            timestamp: r.timestamp,
+            event_code: r.event_code.to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for SystemEventInfo {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        SystemEventInfoRef::try_from(s).map(Self::from)
+    }
+}
@@ -69,3 +69,12 @@ impl Side {
         !self.is_buy()
     }
 }
+
+impl From<Side> for market_datatypes::Side {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Buy => market_datatypes::Side::Buy,
+            Side::Sell => market_datatypes::Side::Sell,
+        }
+    }
+}
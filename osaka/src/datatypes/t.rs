@@ -1,13 +1,15 @@
 // automatically generated
 
-use std::str::FromStr;
-
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::tag_guard;
+use crate::util::Fields;
+use crate::{
+    tag_guard,
+    FieldError,
+};
 
 ///
 /// 6.3.1 秒タグ （タグ ID ： T ）
@@ -35,13 +37,15 @@ impl_message! {
 }
 
 impl TryFrom<&str> for SecondTag {
-    type Error = ();
+    type Error = FieldError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
         tag_guard!('T', s);
-        let mut iter = s.split(",").skip(1);
-        let second = FromStr::from_str(iter.next().ok_or(())?).ok().ok_or(())?;
-        let timestamp = NaiveDateTime::from_timestamp_opt(second, 0).unwrap();
+        let mut iter = Fields::new(s);
+        let second = iter.parse("second")?;
+        let timestamp = chrono::DateTime::from_timestamp(second, 0)
+            .map(|dt| dt.naive_utc())
+            .ok_or_else(|| FieldError::invalid("second", &second.to_string()))?;
         Ok(Self { timestamp, second })
     }
 }
@@ -51,6 +51,14 @@ impl<'a> TryFrom<&'a DeleteOrder> for UniqueId {
 }
 
 
+/// order ids are only unique per side, so the side is packed into the lowest bit.
+/// books are kept per `order_book_id`, it is not part of the key.
+impl tom_orderbook::UniqueOrderId for UniqueId {
+    fn unique_order_id(&self) -> u64 {
+        ((self.order_id as u64) << 1) | self.side.is_sell() as u64
+    }
+}
+
 impl ToString for UniqueId {
     fn to_string(&self) -> String {
         format!("{}-{}-{:?}", self.order_book_id, self.order_id, self.side)
@@ -5,8 +5,10 @@ use std::str::{
 
 use chrono::NaiveDateTime;
 
+use crate::FieldError;
+
 pub fn extract_value<'a>(s: &'a str) -> Option<&'a str> {
-    if let Some((a, b)) = s.find("(").zip(s.find(")")) {
+    if let Some((a, b)) = s.find('(').zip(s.find(')')) {
         return Some(&s[a + 1..b]);
     }
     None
@@ -15,7 +17,7 @@ pub fn extract_value<'a>(s: &'a str) -> Option<&'a str> {
 pub fn extract_value_and_parse<T: FromStr>(s: &str) -> Option<T> {
     if let Ok(t) = FromStr::from_str(s) {
         Some(t)
-    } else if let Some((a, b)) = s.find("(").zip(s.find(")")) {
+    } else if let Some((a, b)) = s.find('(').zip(s.find(')')) {
         (&s[(a + 1)..b]).parse().ok()
     } else {
         None
@@ -23,25 +25,31 @@ pub fn extract_value_and_parse<T: FromStr>(s: &str) -> Option<T> {
 }
 
 pub fn extract_datetime_string(s: &str) -> Option<&str> {
-    if let Some(a) = s.find("(") {
+    if let Some(a) = s.find('(') {
         return Some(&s[..a]);
     }
     None
 }
 
+/// uses the nanoseconds in parentheses, which is much faster than parsing the text
 pub fn extract_datetime<'a>(s: &'a str) -> Option<NaiveDateTime> {
-    if let Some(a) = s.find("(") {
+    if let Some(a) = s.find('(') {
+        if let Some(ns) = extract_value(&s[a..]).and_then(|ns| ns.parse::<i64>().ok()) {
+            let secs = ns.div_euclid(1_000_000_000);
+            let nsecs = ns.rem_euclid(1_000_000_000) as u32;
+            return chrono::DateTime::from_timestamp(secs, nsecs).map(|dt| dt.naive_utc());
+        }
         return NaiveDateTime::parse_from_str(&s[..a], "%Y-%m-%dT%H:%M:%S%.9f").ok();
     }
     None
 }
 
 ///
-/// ```rust
+/// ```rust,ignore
 /// let c = $to_iter.chars().next();
 /// match c {
 ///     Some(i) if $tag == i => (),
-///     _ => return Err(()),
+///     _ => return Err(FieldError::tag(c)),
 /// };
 /// ```
 #[macro_export]
@@ -50,7 +58,53 @@ macro_rules! tag_guard {
         let c = $to_iter.chars().next();
         match c {
             Some(i) if $tag == i => (),
-            _ => return Err(()),
+            _ => return Err($crate::FieldError::tag(c)),
         };
     };
 }
+
+/// Comma separated fields of one line, the tag is skipped
+///
+/// every accessor names the field it reads, so a failure can tell which field broke.
+/// iterating yields the raw fields, which is used to skip reserved fields.
+pub struct Fields<'a> {
+    iter: std::iter::Skip<str::Split<'a, char>>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self {
+            iter: s.split(',').skip(1),
+        }
+    }
+
+    /// the raw field
+    pub fn field(&mut self, field: &'static str) -> Result<&'a str, FieldError> {
+        self.iter.next().ok_or(FieldError::missing(field))
+    }
+
+    pub fn parse<T: FromStr>(&mut self, field: &'static str) -> Result<T, FieldError> {
+        let s = self.field(field)?;
+        s.parse().map_err(|_| FieldError::invalid(field, s))
+    }
+
+    /// parses the value in parentheses, e.g. `176685556` of `PUT_NK225_210312_26000(176685556)`,
+    /// or the whole field if it has none
+    pub fn parse_value<T: FromStr>(&mut self, field: &'static str) -> Result<T, FieldError> {
+        let s = self.field(field)?;
+        extract_value_and_parse(s).ok_or_else(|| FieldError::invalid(field, s))
+    }
+
+    pub fn datetime(&mut self, field: &'static str) -> Result<NaiveDateTime, FieldError> {
+        let s = self.field(field)?;
+        extract_datetime(s).ok_or_else(|| FieldError::invalid(field, s))
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.iter.next()
+    }
+}
@@ -1,2 +1,28 @@
 mod datatypes;
-pub use datatypes::*;
\ No newline at end of file
+pub use datatypes::*;
+mod book_builder;
+pub use book_builder::{
+    BookBuilderError,
+    OffTickOrder,
+    OsakaBookBuilder,
+    PositionDiscrepancy,
+    PositionMode,
+    TickCheck,
+};
+mod reader;
+pub use reader::OsakaReader;
+mod tick_table;
+pub use tick_table::TickTable;
+mod catalog;
+pub use catalog::InstrumentCatalog;
+mod binary;
+pub use binary::{
+    convert_to_binary,
+    BinaryReader,
+    BinaryWriter,
+    Conversion,
+    BINARY_VERSION,
+};
+
+#[cfg(test)]
+mod test;
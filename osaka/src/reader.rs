@@ -0,0 +1,100 @@
+use std::fs::File;
+use std::io::{
+    self,
+    BufRead,
+    BufReader,
+};
+use std::path::Path;
+
+use crate::{
+    MessageEnum,
+    MessageRef,
+    ParseError,
+    ParseErrorKind,
+};
+
+/// Reads the messages of a tag file line by line
+///
+/// every line is parsed by the parser of its leading tag, blank lines are skipped.
+/// errors carry the line number and do not stop the iteration.
+pub struct OsakaReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+}
+
+impl<R: BufRead> OsakaReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+        }
+    }
+
+    /// number of the last line that was read, starts at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// parses the next line without allocating, the message borrows the reader's buffer
+    pub fn next_ref(&mut self) -> Option<Result<MessageRef<'_>, ParseError>> {
+        loop {
+            self.buf.clear();
+            let read = self.reader.read_line(&mut self.buf);
+            if matches!(read, Ok(0)) {
+                return None;
+            }
+            self.line += 1;
+            if let Err(e) = read {
+                return Some(Err(ParseError {
+                    line: self.line,
+                    tag: None,
+                    kind: ParseErrorKind::Io(e),
+                }));
+            }
+            if !self.buf.trim_end_matches(['\n', '\r']).is_empty() {
+                break;
+            }
+        }
+        let line = self.buf.trim_end_matches(['\n', '\r']);
+        Some(MessageRef::from_line(line, self.line))
+    }
+}
+
+impl OsakaReader<Box<dyn BufRead>> {
+    /// opens the file, `.gz` and `.zst` files are decompressed
+    /// if the `gzip` or `zstd` feature is enabled
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let reader: Box<dyn BufRead> = match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "gzip")]
+            Some("gz") => Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(file))),
+            #[cfg(feature = "zstd")]
+            Some("zst") => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+            #[cfg(not(feature = "gzip"))]
+            Some("gz") => return Err(unsupported("gzip")),
+            #[cfg(not(feature = "zstd"))]
+            Some("zst") => return Err(unsupported("zstd")),
+            _ => Box::new(BufReader::new(file)),
+        };
+        Ok(Self::new(reader))
+    }
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn unsupported(feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("enable the {feature} feature to read this file"),
+    )
+}
+
+impl<R: BufRead> Iterator for OsakaReader<R> {
+    type Item = Result<MessageEnum, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_ref().map(|msg| msg.map(MessageRef::into_owned))
+    }
+}
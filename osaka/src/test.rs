@@ -0,0 +1,418 @@
+use chrono::NaiveDateTime;
+use market_datatypes::{
+    OrderPrice,
+    PriceScale,
+};
+use tom_orderbook::OrderBookError;
+
+use crate::{
+    convert_to_binary,
+    BinaryReader,
+    BookBuilderError,
+    BinaryWriter,
+    InstrumentCatalog,
+    MessageEnum,
+    MessageRef,
+    OsakaBookBuilder,
+    OsakaReader,
+    ParseErrorKind,
+    PositionMode,
+    TickCheck,
+    TickTable,
+    UniqueId,
+};
+
+const TS: &str = "2021-03-01T00:06:20.042573706(1614557180042573706)";
+const BOOK: &str = "PUT_NK225_210312_26000(176685556)";
+
+fn add(order_id: i64, side: char, position: i64, qty: i64, price: i64) -> MessageEnum {
+    format!("A,{TS},{order_id},{BOOK},{side},{position},{qty},{price},0,2")
+        .parse()
+        .unwrap()
+}
+
+fn apply(builder: &mut OsakaBookBuilder, msg: &str) -> Result<bool, BookBuilderError> {
+    builder.apply(&msg.parse().unwrap())
+}
+
+#[test]
+fn book_builder() {
+    let mut builder = OsakaBookBuilder::new();
+    builder.apply(&add(1, 'B', 1, 10, 100)).unwrap();
+    builder.apply(&add(2, 'B', 2, 5, 100)).unwrap();
+    // the exchange put this order in front of the others
+    builder.apply(&add(3, 'B', 1, 7, 100)).unwrap();
+    // order ids are only unique per side
+    builder.apply(&add(1, 'S', 1, 4, 110)).unwrap();
+
+    let queue = |builder: &OsakaBookBuilder| -> Vec<(i64, i64)> {
+        let book = builder.book(176685556).unwrap();
+        let level = book.iter_price_level(&market_datatypes::Side::Buy).next();
+        level
+            .map(|level| {
+                level
+                    .iter_orders()
+                    .map(|(_, o)| (o.order_id, o.quantity))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(queue(&builder), vec![(3, 7), (1, 10), (2, 5)]);
+
+    apply(&mut builder, &format!("E,{TS},3,{BOOK},B,2,73967175152436735,0,,")).unwrap();
+    assert_eq!(queue(&builder), vec![(3, 5), (1, 10), (2, 5)]);
+
+    apply(&mut builder, &format!("D,{TS},1,{BOOK},B")).unwrap();
+    assert_eq!(queue(&builder), vec![(3, 5), (2, 5)]);
+
+    let book = builder.book(176685556).unwrap();
+    assert_eq!(book.best_ask().map(|i| i.qty()), Some(4));
+
+    apply(
+        &mut builder,
+        &format!("C,{TS},1,{BOOK},S,4,73967175152437406,0,,,109,N,N"),
+    )
+    .unwrap();
+    assert_eq!(builder.book(176685556).unwrap().best_ask(), None);
+
+    let missing = UniqueId {
+        order_book_id: 176685556,
+        order_id: 1,
+        side: crate::Side::Buy,
+    };
+    assert_eq!(
+        apply(&mut builder, &format!("D,{TS},1,{BOOK},B")),
+        Err(BookBuilderError::Book(OrderBookError::UnknownOrderId(
+            tom_orderbook::UniqueOrderId::unique_order_id(&missing)
+        )))
+    );
+    assert_eq!(
+        builder.book(176685556).unwrap().best_bid().map(|i| i.price()),
+        Some(OrderPrice::Limit(100))
+    );
+}
+
+#[test]
+fn book_builder_reports_position_discrepancies() {
+    let mut builder = OsakaBookBuilder::with_position_mode(PositionMode::Validate);
+    builder.apply(&add(1, 'B', 1, 10, 100)).unwrap();
+    builder.apply(&add(2, 'B', 2, 5, 100)).unwrap();
+    builder.apply(&add(3, 'B', 1, 7, 100)).unwrap();
+    builder.apply(&add(4, 'S', 1, 1, 110)).unwrap();
+
+    let discrepancies = builder.take_discrepancies();
+    assert_eq!(discrepancies.len(), 1);
+    assert_eq!(discrepancies[0].id.order_id, 3);
+    assert_eq!((discrepancies[0].expected, discrepancies[0].actual), (1, 3));
+    assert!(builder.discrepancies().is_empty());
+
+    // the order was moved to the exchange's position
+    let book = builder.book(176685556).unwrap();
+    let level = book.iter_price_level(&market_datatypes::Side::Buy).next().unwrap();
+    let ids: Vec<_> = level.iter_orders().map(|(_, o)| o.order_id).collect();
+    assert_eq!(ids, vec![3, 1, 2]);
+
+    builder.apply(&add(5, 'B', 4, 1, 100)).unwrap();
+    assert!(builder.discrepancies().is_empty());
+}
+
+#[test]
+fn reader_reports_the_failing_line_and_field() {
+    let input = format!(
+        "T,1614557180\n{}\n\nD,{TS},1,{BOOK},X\nQ,{TS}\nE,{TS},3,{BOOK},B\r\nS,{TS},O\n",
+        format_args!("A,{TS},1,{BOOK},B,1,10,100,0,2"),
+    );
+    let mut reader = OsakaReader::new(input.as_bytes());
+
+    assert_eq!(reader.next().unwrap().unwrap().tag(), 'T');
+    assert_eq!(reader.next().unwrap().unwrap().tag(), 'A');
+
+    let e = reader.next().unwrap().unwrap_err();
+    assert_eq!((e.line, e.tag, e.field()), (4, Some('D'), Some("side")));
+
+    let e = reader.next().unwrap().unwrap_err();
+    assert_eq!((e.line, e.tag), (5, Some('Q')));
+    assert!(matches!(e.kind, ParseErrorKind::UnknownTag));
+
+    let e = reader.next().unwrap().unwrap_err();
+    assert_eq!((e.line, e.field()), (6, Some("executed_quantity")));
+    assert_eq!(e.to_string(), "line 6 (tag E): missing executed_quantity");
+
+    assert_eq!(reader.next().unwrap().unwrap().tag(), 'S');
+    assert!(reader.next().is_none());
+    assert_eq!(reader.line(), 7);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn reader_opens_gzip_files() {
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("osaka-reader-{}.gz", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    writeln!(encoder, "A,{TS},1,{BOOK},B,1,10,100,0,2").unwrap();
+    encoder.finish().unwrap();
+
+    let messages: Vec<_> = OsakaReader::open(&path).unwrap().collect();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].as_ref().unwrap().tag(), 'A');
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn reader_opens_zstd_files() {
+    let path = std::env::temp_dir().join(format!("osaka-reader-{}.zst", std::process::id()));
+    let data = zstd::encode_all(format!("A,{TS},1,{BOOK},B,1,10,100,0,2\n").as_bytes(), 0).unwrap();
+    std::fs::write(&path, data).unwrap();
+
+    let messages: Vec<_> = OsakaReader::open(&path).unwrap().collect();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].as_ref().unwrap().tag(), 'A');
+}
+
+#[test]
+fn message_ref_borrows_from_the_line() {
+    let line = "R,2021-02-28T21:07:50.931282000(1614546470931282000),40632820,PUT_NK225_210910_28000,186098018,186098018,1,JPY,4,0,0,1,0,0,0,500,28000,20210910,0,2";
+    let msg = MessageRef::from_line(line, 1).unwrap();
+    match msg {
+        MessageRef::ProductInfo(info) => {
+            assert_eq!(info.symbol, "PUT_NK225_210910_28000");
+            let offset = line.find("PUT_").unwrap();
+            assert!(std::ptr::eq(info.symbol.as_ptr(), line[offset..].as_ptr()));
+        }
+        _ => panic!("{msg:?}"),
+    }
+    assert_eq!(msg.tag(), 'R');
+    assert_eq!(
+        msg.timestamp(),
+        NaiveDateTime::parse_from_str("2021-02-28T21:07:50.931282000", "%Y-%m-%dT%H:%M:%S%.9f")
+            .unwrap()
+    );
+    assert_eq!(msg.into_owned(), line.parse::<MessageEnum>().unwrap());
+
+    let input = format!("E,{TS},3,{BOOK},B,2,73967175152436735,0,,\n");
+    let mut reader = OsakaReader::new(input.as_bytes());
+    match reader.next_ref() {
+        Some(Ok(MessageRef::Executed(exec))) => assert_eq!(exec.match_id, "73967175152436735"),
+        other => panic!("{other:?}"),
+    }
+    assert!(reader.next_ref().is_none());
+}
+
+#[test]
+fn binary_encoding_round_trips() {
+    // every sample line of the parser tests
+    let text: String = include_str!("datatypes/test.rs")
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .filter_map(|line| line.strip_prefix('"')?.strip_suffix('"'))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    let messages: Vec<MessageEnum> = OsakaReader::new(text.as_bytes())
+        .filter_map(Result::ok)
+        .collect();
+    let tags: std::collections::BTreeSet<_> = messages.iter().map(|msg| msg.tag()).collect();
+    assert!(tags.len() >= 10, "{tags:?}");
+
+    let mut binary = vec![];
+    let conversion = convert_to_binary(OsakaReader::new(text.as_bytes()), &mut binary).unwrap();
+    assert_eq!(conversion.messages, messages.len() as u64);
+    assert!(binary.len() * 2 < text.len());
+
+    let decoded: Vec<_> = BinaryReader::new(binary.as_slice())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(decoded, messages);
+
+    // a symbol is only stored once
+    let msg: MessageEnum = format!("O,{TS},{BOOK},M_PRE_OPEN").parse().unwrap();
+    let mut writer = BinaryWriter::new(vec![]).unwrap();
+    writer.write(&msg).unwrap();
+    let first = writer.into_inner().unwrap().len();
+    let mut writer = BinaryWriter::new(vec![]).unwrap();
+    writer.write(&msg).unwrap();
+    writer.write(&msg).unwrap();
+    let buf = writer.into_inner().unwrap();
+    assert_eq!(buf.len() - first, 1 + 8 + 8 + 4);
+    assert_eq!(
+        BinaryReader::new(buf.as_slice()).unwrap().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![msg.clone(), msg]
+    );
+
+    let mut other_version = buf.clone();
+    other_version[4] = crate::BINARY_VERSION + 1;
+    assert!(BinaryReader::new(other_version.as_slice()).is_err());
+    // truncated record
+    let mut reader = BinaryReader::new(&buf[..buf.len() - 1]).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().unwrap().is_err());
+}
+
+#[test]
+fn binary_encoding_error_keeps_symbols_consistent() {
+    let line = "R,2021-02-28T21:07:50.931282000(1614546470931282000),40632820,PUT_NK225_210910_28000,186098018,186098018,1,JPY,4,0,0,1,0,0,0,500,28000,20210910,0,2";
+    let msg: MessageEnum = line.parse().unwrap();
+    let mut too_long = msg.clone();
+    match &mut too_long {
+        MessageEnum::ProductInfo(info) => info.long_name = "x".repeat(u16::MAX as usize + 1),
+        other => panic!("{other:?}"),
+    }
+
+    // the symbol is interned before the long name fails to encode
+    let mut writer = BinaryWriter::new(vec![]).unwrap();
+    assert!(writer.write(&too_long).is_err());
+    writer.write(&msg).unwrap();
+    let buf = writer.into_inner().unwrap();
+    assert_eq!(
+        BinaryReader::new(buf.as_slice()).unwrap().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![msg]
+    );
+}
+
+#[test]
+fn instrument_catalog() {
+    let lines = [
+        "R,2021-02-28T21:07:50.931282000(1614546470931282000),40632820,PUT_NK225_210910_28000,186098018,186098018,1,JPY,4,0,0,1,0,0,0,500,28000,20210910,0,2",
+        "R,2021-02-28T21:07:50.931282000(1614546470931282000),264700404,CAL_NK225_231208_14250,198124218,198124218,1,JPY,4,0,0,1,0,0,0,500,14250,20231208,0,1",
+        "R,2021-02-28T21:07:50.931282000(1614546470931282000),223347188,CAL_NK225_210910_29500,196099518,196099518,1,JPY,4,0,0,1,0,0,0,500,29500,20210910,0,1",
+        "R,2021-02-28T21:07:50.931282000(1614546470931282000),90636788,CAL_NK225_210910_28000,146091018,146091018,1,JPY,4,0,0,1,0,0,0,500,28000,20210910,0,1",
+        "R,2021-03-30T21:14:49.816929242(1617138889816929242),590334,FUT_NK225M_2109,166090019,166090019,3,JPY,4,0,0,1,0,0,0,500,0,20210910,0,0",
+        "L,2021-02-28T21:07:50.931282000(1614546470931282000),PUT_NK225_210910_28000(40632820),50000,1000000,9999999",
+        "L,2021-02-28T21:07:50.931282000(1614546470931282000),PUT_NK225_210910_28000(40632820),10000,10000,999999",
+        "L,2021-02-28T21:07:50.931282000(1614546470931282000),PUT_NK225_210910_28000(40632820),100000,10000000,999900000",
+        "M,2021-02-28T21:07:50.931282000(1614546470931282000),77,40632820,B,1",
+        "M,2021-02-28T21:07:50.931282000(1614546470931282000),77,90636788,S,2",
+        "A,2021-02-28T21:07:50.931282000(1614546470931282000),1,PUT_NK225_210910_28000(40632820),B,1,1,10000,0,2",
+    ];
+    let mut catalog = InstrumentCatalog::new();
+    let used = lines
+        .iter()
+        .filter(|line| catalog.apply(&line.parse().unwrap()))
+        .count();
+    assert_eq!(used, lines.len() - 1);
+    assert_eq!(catalog.len(), 5);
+
+    assert_eq!(catalog.symbol(40632820), Some("PUT_NK225_210910_28000"));
+    assert_eq!(catalog.symbol(1), None);
+    assert_eq!(catalog.price_decimals(590334), Some(4));
+    assert_eq!(catalog.price_scale(590334), Some(PriceScale::new(4)));
+    assert_eq!(
+        catalog.fixed_price(590334, 285000000).unwrap().to_string(),
+        "28500.0000"
+    );
+    assert_eq!(catalog.fixed_price(1, 285000000), None);
+    assert_eq!(
+        catalog.product(40632820).unwrap().strike(),
+        Some(PriceScale::new(0).price(28000))
+    );
+
+    assert_eq!(catalog.tick_size_at(40632820, 9999), None);
+    assert_eq!(catalog.tick_size_at(40632820, 10000), Some(10000));
+    assert_eq!(catalog.tick_size_at(40632820, 999999), Some(10000));
+    assert_eq!(catalog.tick_size_at(40632820, 1000000), Some(50000));
+    assert_eq!(catalog.tick_size_at(40632820, 999900000), Some(100000));
+    assert_eq!(catalog.tick_size_at(40632820, 999900001), None);
+    assert_eq!(catalog.tick_size_at(590334, 10000), None);
+
+    let legs: Vec<_> = catalog
+        .legs(77)
+        .iter()
+        .map(|leg| (leg.leg_order_book_id, leg.leg_side, leg.leg_ratio))
+        .collect();
+    assert_eq!(
+        legs,
+        vec![(40632820, crate::Side::Buy, 1), (90636788, crate::Side::Sell, 2)]
+    );
+    assert!(catalog.legs(40632820).is_empty());
+
+    let chain: Vec<_> = catalog
+        .options_chain(500, 20210910)
+        .iter()
+        .map(|info| info.symbol.as_str())
+        .collect();
+    assert_eq!(
+        chain,
+        vec![
+            "CAL_NK225_210910_28000",
+            "PUT_NK225_210910_28000",
+            "CAL_NK225_210910_29500"
+        ]
+    );
+
+    // a new listing of the same order book replaces the old one
+    catalog.apply(&lines[1].replace("20231208,0,1", "20210910,0,1").parse().unwrap());
+    assert_eq!(catalog.options_chain(500, 20210910).len(), 4);
+    assert!(catalog.options_chain(500, 20231208).is_empty());
+}
+
+fn tick(price_from: i64, price_to: i64, tick_size: i64) -> String {
+    format!("L,{TS},{BOOK},{tick_size},{price_from},{price_to}")
+}
+
+#[test]
+fn tick_table_rounds_to_the_grid() {
+    let table: TickTable = [
+        tick(10000, 999999, 10000),
+        tick(1000000, 9999999, 50000),
+        tick(10000000, 999900000, 100000),
+    ]
+    .iter()
+    .map(|line| line.parse::<MessageEnum>().unwrap().try_into().unwrap())
+    .collect();
+
+    assert!(table.is_on_tick(990000));
+    assert!(!table.is_on_tick(995000));
+    assert!(!table.is_on_tick(1010000));
+    assert!(!table.is_on_tick(0));
+
+    assert_eq!(table.round_down(1049999), Some(1000000));
+    assert_eq!(table.round_up(1000001), Some(1050000));
+    assert_eq!(table.round_up(995000), Some(1000000));
+    assert_eq!(table.round_down(9999999), Some(9950000));
+    assert_eq!(table.round_up(9999999), Some(10000000));
+    assert_eq!(table.round_down(5000), None);
+    assert_eq!(table.round_up(5000), Some(10000));
+    assert_eq!(table.round_up(999900001), None);
+    assert_eq!(table.round_down(1999900000), Some(999900000));
+
+    assert_eq!(table.step(990000, 2), Some(1050000));
+    assert_eq!(table.step(1050000, -2), Some(990000));
+    assert_eq!(table.step(995000, 1), Some(1000000));
+    assert_eq!(table.step(995000, -1), Some(990000));
+    assert_eq!(table.step(990000, 0), Some(990000));
+    assert_eq!(table.step(20000, -2), None);
+}
+
+#[test]
+fn book_builder_checks_ticks() {
+    let mut builder = OsakaBookBuilder::new();
+    builder.set_tick_check(TickCheck::Flag);
+    // no tick sizes yet
+    builder.apply(&add(1, 'B', 1, 1, 15000)).unwrap();
+    builder.apply(&tick(10000, 999999, 10000).parse().unwrap()).unwrap();
+    builder.apply(&add(2, 'B', 1, 1, 20000)).unwrap();
+    builder.apply(&add(3, 'B', 1, 1, 25000)).unwrap();
+    let flagged = builder.take_off_tick_orders();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!((flagged[0].id.order_id, flagged[0].price), (3, 25000));
+
+    builder.set_tick_check(TickCheck::Reject);
+    let id = UniqueId {
+        order_book_id: 176685556,
+        order_id: 4,
+        side: crate::Side::Buy,
+    };
+    assert_eq!(
+        builder.apply(&add(4, 'B', 1, 1, 25000)),
+        Err(BookBuilderError::OffTick {
+            id,
+            price: 25000
+        })
+    );
+    assert!(builder.off_tick_orders().is_empty());
+    assert_eq!(builder.book(176685556).unwrap().order_count(), 3);
+}
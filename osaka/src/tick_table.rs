@@ -0,0 +1,127 @@
+use crate::TickSize;
+
+/// Tick size bands of one order book
+///
+/// a price is on the grid if it is inside a band and a multiple of the band's tick size.
+/// both ends of a band are inclusive.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TickTable {
+    /// sorted by `price_from`
+    bands: Vec<TickSize>,
+}
+
+impl TickTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a band with the same `price_from` is replaced
+    pub fn insert(&mut self, band: TickSize) {
+        match self
+            .bands
+            .binary_search_by_key(&band.price_from, |b| b.price_from)
+        {
+            Ok(idx) => self.bands[idx] = band,
+            Err(idx) => self.bands.insert(idx, band),
+        }
+    }
+
+    /// bands sorted by `price_from`
+    pub fn bands(&self) -> &[TickSize] {
+        &self.bands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bands.is_empty()
+    }
+
+    fn band_idx(&self, price: i64) -> Option<usize> {
+        let idx = self
+            .bands
+            .partition_point(|b| b.price_from <= price)
+            .checked_sub(1)?;
+        (price <= self.bands[idx].price_to).then_some(idx)
+    }
+
+    /// tick size of the band that contains the price
+    pub fn tick_at(&self, price: i64) -> Option<i64> {
+        Some(self.bands[self.band_idx(price)?].tick_size)
+    }
+
+    pub fn is_on_tick(&self, price: i64) -> bool {
+        self.tick_at(price)
+            .is_some_and(|tick| tick > 0 && price % tick == 0)
+    }
+
+    /// highest valid price that is not above `price`
+    pub fn round_down(&self, price: i64) -> Option<i64> {
+        let idx = match self.band_idx(price) {
+            Some(idx) => idx,
+            // above the last band or in a gap between bands
+            None => {
+                let idx = self
+                    .bands
+                    .partition_point(|b| b.price_from <= price)
+                    .checked_sub(1)?;
+                return self.round_down(self.bands[idx].price_to);
+            }
+        };
+        let band = &self.bands[idx];
+        if band.tick_size <= 0 {
+            return None;
+        }
+        let rounded = price.div_euclid(band.tick_size) * band.tick_size;
+        if rounded >= band.price_from {
+            Some(rounded)
+        } else {
+            self.round_down(band.price_from - 1)
+        }
+    }
+
+    /// lowest valid price that is not below `price`
+    pub fn round_up(&self, price: i64) -> Option<i64> {
+        let idx = match self.band_idx(price) {
+            Some(idx) => idx,
+            // below the first band or in a gap between bands
+            None => {
+                let next = self.bands.partition_point(|b| b.price_from <= price);
+                return self.round_up(self.bands.get(next)?.price_from);
+            }
+        };
+        let band = &self.bands[idx];
+        if band.tick_size <= 0 {
+            return None;
+        }
+        let rounded = -((-price).div_euclid(band.tick_size) * band.tick_size);
+        if rounded <= band.price_to {
+            Some(rounded)
+        } else {
+            self.round_up(band.price_to.checked_add(1)?)
+        }
+    }
+
+    /// moves `ticks` valid prices up, or down if negative.
+    /// an off-tick price is rounded in the direction of the step first, which counts as one tick.
+    /// `None` if the grid ends before.
+    pub fn step(&self, price: i64, ticks: i64) -> Option<i64> {
+        let mut price = price;
+        for _ in 0..ticks.unsigned_abs() {
+            price = if ticks > 0 {
+                self.round_up(price.checked_add(1)?)?
+            } else {
+                self.round_down(price.checked_sub(1)?)?
+            };
+        }
+        Some(price)
+    }
+}
+
+impl FromIterator<TickSize> for TickTable {
+    fn from_iter<I: IntoIterator<Item = TickSize>>(iter: I) -> Self {
+        let mut table = Self::new();
+        for band in iter {
+            table.insert(band);
+        }
+        table
+    }
+}
@@ -0,0 +1,23 @@
+use crate::PriceQty;
+
+/// Aggregated view of the top of the book (L2)
+///
+/// both sides are sorted best price first, i.e. bids descending and asks ascending
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepthSnapshot<P = i64, Q = i64>
+where
+    P: Copy,
+{
+    pub bids: Vec<PriceQty<P, Q>>,
+    pub asks: Vec<PriceQty<P, Q>>,
+}
+
+impl<P: Copy, Q> Default for DepthSnapshot<P, Q> {
+    fn default() -> Self {
+        Self {
+            bids: vec![],
+            asks: vec![],
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use market_datatypes::{OrderPrice, Side};
+
+/// Errors returned by the mutating methods of `OrderBook`
+///
+/// Replays of exchange data routinely reference orders that were placed before the file starts,
+/// so callers usually want to count `UnknownOrderId` and carry on rather than bail out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderBookError<P = i64, Q = i64>
+where
+    P: Copy,
+{
+    /// no order with this id is on the book
+    UnknownOrderId(u64),
+    /// an order with this id is already on the book
+    DuplicateOrderId(u64),
+    /// the order is indexed at a price level that is not on the book
+    PriceLevelMissing {
+        price: OrderPrice<P>,
+        side: Side,
+    },
+    NegativeQty {
+        order_id: u64,
+        qty: Q,
+    },
+    /// an execution is larger than the quantity of the maker order
+    Overfill {
+        order_id: u64,
+        qty: Q,
+        executed: Q,
+    },
+    /// the order is on the other side of the book than the caller expected
+    SideMismatch {
+        order_id: u64,
+        expected: Side,
+        found: Side,
+    },
+    /// adding the order would cross the book, see `CrossingPolicy::Reject`
+    CrossedBook {
+        order_id: u64,
+    },
+}
+
+impl<P: Copy, Q> OrderBookError<P, Q> {
+    pub fn is_unknown_order_id(&self) -> bool {
+        matches!(self, Self::UnknownOrderId(_))
+    }
+}
+
+impl<P: Copy + fmt::Debug, Q: fmt::Display> fmt::Display for OrderBookError<P, Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOrderId(id) => write!(f, "unknown order id {id}"),
+            Self::DuplicateOrderId(id) => write!(f, "order id {id} is already on the book"),
+            Self::PriceLevelMissing { price, side } => {
+                write!(f, "no {side:?} price level at {price:?}")
+            }
+            Self::NegativeQty { order_id, qty } => {
+                write!(f, "negative quantity {qty} for order {order_id}")
+            }
+            Self::Overfill {
+                order_id,
+                qty,
+                executed,
+            } => write!(
+                f,
+                "execution of {executed} exceeds the quantity {qty} of order {order_id}"
+            ),
+            Self::SideMismatch {
+                order_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "order {order_id} is on the {found:?} side, expected {expected:?}"
+            ),
+            Self::CrossedBook { order_id } => write!(f, "order {order_id} would cross the book"),
+        }
+    }
+}
+
+impl<P: Copy + fmt::Debug, Q: fmt::Debug + fmt::Display> std::error::Error
+    for OrderBookError<P, Q>
+{
+}
@@ -0,0 +1,28 @@
+use market_datatypes::{OrderPrice, Side};
+
+/// Execution of a resting order reported by the exchange
+pub trait Execution<P = i64, Q = i64> {
+    fn maker_id(&self) -> u64;
+    fn size(&self) -> Q;
+    /// price the execution happened at, if the feed reports it.
+    /// `None` means the price of the maker order.
+    fn price(&self) -> Option<P> {
+        None
+    }
+}
+
+/// Trade that results from applying an `Execution` to the book
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trade<P = i64, Q = i64>
+where
+    P: Copy,
+{
+    pub maker_id: u64,
+    /// side of the maker order
+    pub side: Side,
+    pub price: OrderPrice<P>,
+    pub qty: Q,
+    /// quantity the maker order has left, the order was removed from the book if this is 0
+    pub remaining_qty: Q,
+}
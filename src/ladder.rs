@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+
+use num_traits::{Bounded, CheckedSub, FromPrimitive, ToPrimitive, Zero};
+use slab::Slab;
+
+use crate::*;
+
+/// number of ticks covered by the array part of the ladder
+const WIDTH: usize = 1024;
+/// sides with fewer levels only use the tree, most books of a market are shallow
+const MIN_LEVELS: usize = 16;
+const EMPTY: u32 = u32::MAX;
+
+/// Limit price levels of one side of the book
+///
+/// levels within `WIDTH` ticks of `base` are found by their distance from `base`,
+/// the rest is kept in a tree. one tick is one unit of the price type,
+/// prices that are not a whole number of units away from `base` go to the tree as well.
+/// the window is moved to the touch when a new best price falls outside of it
+/// and is only allocated once the side has `MIN_LEVELS` levels.
+pub(crate) struct Ladder<O: Order> {
+    side: Side,
+    levels: Slab<PriceLevel<O>>,
+    /// price of `slots[0]`
+    base: O::Price,
+    /// key into `levels` for every tick from `base`, empty until the first level is added
+    slots: Vec<u32>,
+    /// number of occupied slots
+    near: usize,
+    /// lowest and highest occupied slot, only valid while `near > 0`
+    lo: usize,
+    hi: usize,
+    /// levels outside of the window
+    far: BTreeMap<O::Price, usize>,
+}
+
+impl<O: Order> Ladder<O> {
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            levels: Slab::new(),
+            base: O::Price::zero(),
+            slots: vec![],
+            near: 0,
+            lo: 0,
+            hi: 0,
+            far: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, price: &O::Price) -> Option<&PriceLevel<O>> {
+        let key = self.key(price)?;
+        Some(&self.levels[key])
+    }
+
+    pub fn get_mut(&mut self, price: &O::Price) -> Option<&mut PriceLevel<O>> {
+        let key = self.key(price)?;
+        Some(&mut self.levels[key])
+    }
+
+    /// adds a level for a price that is not on the ladder yet
+    pub fn insert(&mut self, level: PriceLevel<O>) {
+        let price = level.price_min_if_market();
+        if self.slot(&price).is_none()
+            && self.levels.len() >= MIN_LEVELS
+            && self.is_new_touch(&price)
+        {
+            self.recenter(price);
+        }
+        let key = self.levels.insert(level);
+        match self.slot(&price) {
+            Some(idx) => self.occupy(idx, key),
+            None => {
+                self.far.insert(price, key);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, price: &O::Price) -> Option<PriceLevel<O>> {
+        let key = match self.slot(price) {
+            Some(idx) if self.slots[idx] != EMPTY => {
+                let key = self.slots[idx] as usize;
+                self.vacate(idx);
+                key
+            }
+            Some(_) => return None,
+            None => self.far.remove(price)?,
+        };
+        Some(self.levels.remove(key))
+    }
+
+    pub fn best(&self) -> Option<&PriceLevel<O>> {
+        let key = self.best_key()?;
+        Some(&self.levels[key])
+    }
+
+    pub fn best_mut(&mut self) -> Option<&mut PriceLevel<O>> {
+        let key = self.best_key()?;
+        Some(&mut self.levels[key])
+    }
+
+    /// iterates the levels starting from the best price
+    pub fn iter(&self) -> impl Iterator<Item = &PriceLevel<O>> + '_ {
+        let range = if self.near > 0 {
+            self.lo..self.hi + 1
+        } else {
+            0..0
+        };
+        let near = self.slots[range]
+            .iter()
+            .filter(|key| **key != EMPTY)
+            .map(|key| &self.levels[*key as usize]);
+        let far = self.far.values().map(|key| &self.levels[*key]);
+        type Levels<'a, O> = Box<dyn Iterator<Item = &'a PriceLevel<O>> + 'a>;
+        let (near, far): (Levels<O>, Levels<O>) = match self.side {
+            Side::Buy => (Box::new(near.rev()), Box::new(far.rev())),
+            Side::Sell => (Box::new(near), Box::new(far)),
+        };
+        let (mut near, mut far) = (near.peekable(), far.peekable());
+        std::iter::from_fn(move || {
+            let from_far = match (near.peek(), far.peek()) {
+                (Some(n), Some(f)) => {
+                    self.is_better(&f.price_min_if_market(), &n.price_min_if_market())
+                }
+                (None, Some(_)) => true,
+                _ => false,
+            };
+            if from_far {
+                far.next()
+            } else {
+                near.next()
+            }
+        })
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.side);
+    }
+
+    /// index of the slot for the price, if the price is within the window
+    fn slot(&self, price: &O::Price) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let diff = price.checked_sub(&self.base)?;
+        let idx = diff.to_usize()?;
+        (idx < WIDTH && O::Price::from_usize(idx) == Some(diff)).then_some(idx)
+    }
+
+    fn key(&self, price: &O::Price) -> Option<usize> {
+        match self.slot(price) {
+            Some(idx) => (self.slots[idx] != EMPTY).then_some(self.slots[idx] as usize),
+            None => self.far.get(price).copied(),
+        }
+    }
+
+    fn best_key(&self) -> Option<usize> {
+        let near = match self.side {
+            Side::Buy => self.hi,
+            Side::Sell => self.lo,
+        };
+        let near = if self.near > 0 {
+            Some(self.slots[near] as usize)
+        } else {
+            None
+        };
+        let far = match self.side {
+            Side::Buy => self.far.last_key_value(),
+            Side::Sell => self.far.first_key_value(),
+        };
+        match (near, far) {
+            (Some(near), Some((price, far))) => {
+                let near_price = self.levels[near].price_min_if_market();
+                Some(if self.is_better(price, &near_price) {
+                    *far
+                } else {
+                    near
+                })
+            }
+            (near, far) => near.or(far.map(|(_, key)| *key)),
+        }
+    }
+
+    /// true if `a` is a better price than `b` for this side
+    fn is_better(&self, a: &O::Price, b: &O::Price) -> bool {
+        match self.side {
+            Side::Buy => a > b,
+            Side::Sell => a < b,
+        }
+    }
+
+    /// true if the price would be better than every level in the window
+    fn is_new_touch(&self, price: &O::Price) -> bool {
+        if self.near == 0 {
+            return true;
+        }
+        let best = match self.side {
+            Side::Buy => self.hi,
+            Side::Sell => self.lo,
+        };
+        let best = self.levels[self.slots[best] as usize].price_min_if_market();
+        self.is_better(price, &best)
+    }
+
+    /// moves the window so that `price` is in the middle of it
+    fn recenter(&mut self, price: O::Price) {
+        let base = O::Price::from_usize(WIDTH / 2)
+            .and_then(|half| price.checked_sub(&half))
+            .unwrap_or_else(O::Price::min_value);
+        if self.near > 0 {
+            for idx in self.lo..self.hi + 1 {
+                let key = std::mem::replace(&mut self.slots[idx], EMPTY);
+                if key != EMPTY {
+                    let price = self.levels[key as usize].price_min_if_market();
+                    self.far.insert(price, key as usize);
+                }
+            }
+        }
+        self.slots.resize(WIDTH, EMPTY);
+        self.near = 0;
+        self.base = base;
+
+        let mut moved = vec![];
+        for (price, key) in self.far.range(base..) {
+            let in_window = price
+                .checked_sub(&base)
+                .and_then(|diff| diff.to_usize())
+                .is_some_and(|idx| idx < WIDTH);
+            if !in_window {
+                break;
+            }
+            if let Some(idx) = self.slot(price) {
+                moved.push((*price, idx, *key));
+            }
+        }
+        for (price, idx, key) in moved {
+            self.far.remove(&price);
+            self.occupy(idx, key);
+        }
+    }
+
+    fn occupy(&mut self, idx: usize, key: usize) {
+        self.slots[idx] = key as u32;
+        if self.near == 0 {
+            (self.lo, self.hi) = (idx, idx);
+        } else {
+            self.lo = self.lo.min(idx);
+            self.hi = self.hi.max(idx);
+        }
+        self.near += 1;
+    }
+
+    fn vacate(&mut self, idx: usize) {
+        self.slots[idx] = EMPTY;
+        self.near -= 1;
+        if self.near == 0 {
+            return;
+        }
+        if idx == self.lo {
+            while self.slots[self.lo] == EMPTY {
+                self.lo += 1;
+            }
+        }
+        if idx == self.hi {
+            while self.slots[self.hi] == EMPTY {
+                self.hi -= 1;
+            }
+        }
+    }
+}
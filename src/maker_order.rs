@@ -0,0 +1,43 @@
+use market_datatypes::{OrderPrice, Side};
+
+use crate::{BookPrice, BookQty, Order, UniqueOrderId};
+
+/// Order that bridges between the origianl order and the order within the order book
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MakerOrder<P = i64, Q = i64>
+where
+    P: Copy,
+{
+    pub id: u64,
+    pub price: OrderPrice<P>,
+    pub qty: Q,
+    pub side: Side,
+}
+
+impl<P: Copy, Q> UniqueOrderId for MakerOrder<P, Q> {
+    fn unique_order_id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<P: BookPrice, Q: BookQty> Order for MakerOrder<P, Q> {
+    type Price = P;
+    type Qty = Q;
+
+    fn price(&self) -> OrderPrice<P> {
+        self.price
+    }
+
+    fn qty(&self) -> Q {
+        self.qty
+    }
+
+    fn side(&self) -> Side {
+        self.side
+    }
+
+    fn set_qty(&mut self, qty: Q) {
+        self.qty = qty;
+    }
+}
@@ -0,0 +1,39 @@
+use num_traits::Zero;
+
+/// How `OrderBook::add` treats an order that crosses the opposite side of the book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CrossingPolicy {
+    /// rest the order at its price level even if the book becomes crossed.
+    /// this is what you want when replaying MBO data, the exchange already did the matching.
+    #[default]
+    Rest,
+    /// refuse orders that would cross the book with `OrderBookError::CrossedBook`
+    Reject,
+    /// match the order against the opposite side in price-time priority and rest the residual.
+    Match,
+}
+
+/// A single trade between a resting (maker) order and an incoming (taker) order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill<P = i64, Q = i64> {
+    pub maker_id: u64,
+    pub taker_id: u64,
+    /// price of the maker's price level
+    pub price: P,
+    pub qty: Q,
+}
+
+/// Result of `OrderBook::add`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddOutcome<P = i64, Q = i64> {
+    /// fills in the order they happened, always empty unless `CrossingPolicy::Match` is used
+    pub fills: Vec<Fill<P, Q>>,
+    /// quantity left on the book after matching, 0 if the order was fully filled
+    pub resting_qty: Q,
+}
+
+impl<P, Q: Zero + Copy> AddOutcome<P, Q> {
+    pub fn filled_qty(&self) -> Q {
+        self.fills.iter().fold(Q::zero(), |sum, f| sum + f.qty)
+    }
+}
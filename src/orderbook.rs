@@ -1,104 +1,593 @@
+use num_traits::{ToPrimitive, Zero};
+
 use crate::*;
 
+/// error of the book with the price and quantity types of `O`
+pub(crate) type Error<O> = OrderBookError<<O as Order>::Price, <O as Order>::Qty>;
+type Level<O> = PriceQty<<O as Order>::Price, <O as Order>::Qty>;
 
-pub struct OrderBook<O: Order> {
+pub struct OrderBook<O: Order = MakerOrder> {
     order_book_id: u64,
-    /// stack for ask orders
-    ask_orders: VecDeque<PriceLevel<O>>,
-    /// stack for bid orders
-    bid_orders: VecDeque<PriceLevel<O>>,
+    /// limit price levels of ask orders
+    ask_orders: Ladder<O>,
+    /// limit price levels of bid orders
+    bid_orders: Ladder<O>,
     /// allows you to look up the location of the order by it's id
-    order_lookup: HashMap<OrderId, (Price, Side)>,
+    order_lookup: HashMap<OrderId, (OrderPrice<O::Price>, Side)>,
+    ask_market_orders: PriceLevel<O>,
+    bid_market_orders: PriceLevel<O>,
+    crossing_policy: CrossingPolicy,
 }
 
+impl<O: Order> Default for OrderBook<O> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
 
 impl<O: Order> OrderBook<O> {
     pub fn order_book_id(&self) -> u64 {
-        self.order_book_id.clone()
+        self.order_book_id
     }
 
     pub fn new(order_book_id: u64) -> Self {
         Self {
             order_book_id,
-            ask_orders: Default::default(),
-            bid_orders: Default::default(),
+            ask_orders: Ladder::new(Side::Sell),
+            bid_orders: Ladder::new(Side::Buy),
             order_lookup: Default::default(),
+            ask_market_orders: Default::default(),
+            bid_market_orders: Default::default(),
+            crossing_policy: Default::default(),
         }
     }
 
-    fn mut_price_level(&mut self, price: &i64, side: &Side) -> Result<&mut PriceLevel<O>, usize> {
-        let stack = match side {
-            Side::Buy => &mut self.ask_orders,
-            Side::Sell => &mut self.bid_orders,
-        };
-        let item = stack.binary_search_by(|i| i.price.cmp(&price));
-        match item {
-            Ok(idx) => Ok(&mut stack[idx]),
-            Err(idx) => Err(idx),
+    /// creates an order book that matches crossing orders instead of resting them
+    pub fn new_matching(order_book_id: u64) -> Self {
+        let mut data = Self::new(order_book_id);
+        data.crossing_policy = CrossingPolicy::Match;
+        data
+    }
+
+    pub fn crossing_policy(&self) -> CrossingPolicy {
+        self.crossing_policy
+    }
+
+    pub fn set_crossing_policy(&mut self, policy: CrossingPolicy) {
+        self.crossing_policy = policy;
+    }
+
+    fn mut_price_level(
+        &mut self,
+        price: &OrderPrice<O::Price>,
+        side: &Side,
+    ) -> Option<&mut PriceLevel<O>> {
+        match price {
+            OrderPrice::Limit(price) => self.mut_ladder(side).get_mut(price),
+            OrderPrice::Market => match side {
+                Side::Buy => Some(&mut self.bid_market_orders),
+                Side::Sell => Some(&mut self.ask_market_orders),
+            },
         }
     }
 
-    /// iterates the price level with PriceQty
-    pub fn iter_orders<'a>(&'a self, side: &Side) -> impl Iterator<Item = PriceQty> + 'a {
-        let iter = match side {
-            Side::Buy => self.bid_orders.iter(),
-            Side::Sell => self.ask_orders.iter(),
-        };
-        iter.map(|i| i.price_qty())
+    fn price_level(&self, price: &OrderPrice<O::Price>, side: &Side) -> Option<&PriceLevel<O>> {
+        match price {
+            OrderPrice::Limit(price) => self.ladder(side).get(price),
+            OrderPrice::Market => Some(self.market_orders(side)),
+        }
     }
 
-    /// iterates the price level
-    pub fn iter_price_level<'a>(&'a self, side: &Side) -> impl Iterator<Item = &PriceLevel<O>> + 'a {
-        let iter = match side {
-            Side::Buy => self.bid_orders.iter(),
-            Side::Sell => self.ask_orders.iter(),
-        };
-        iter
-    }
-
-    pub fn add(&mut self, order: O) {
-        let side = order.side();
-        match self.mut_price_level(&order.price(), &side) {
-            Ok(level) => level.add(order),
-            Err(idx) => {
-                let val = PriceLevel::new_with_order(order);
-                match side {
-                    Side::Buy => self
-                        .bid_orders
-                        .insert(idx, val),
-                    Side::Sell => self
-                        .ask_orders
-                        .insert(idx, val),
-                };
-            }
+    fn ladder(&self, side: &Side) -> &Ladder<O> {
+        match side {
+            Side::Buy => &self.bid_orders,
+            Side::Sell => &self.ask_orders,
+        }
+    }
+
+    fn mut_ladder(&mut self, side: &Side) -> &mut Ladder<O> {
+        match side {
+            Side::Buy => &mut self.bid_orders,
+            Side::Sell => &mut self.ask_orders,
+        }
+    }
+
+    /// true if the order is resting on the book
+    pub fn contains_order(&self, id: OrderId) -> bool {
+        self.order_lookup.contains_key(&id)
+    }
+
+    /// the resting order with the id
+    pub fn order(&self, id: OrderId) -> Option<&O> {
+        let (price, side) = self.order_lookup.get(&id)?;
+        self.price_level(price, side)?.order(id)
+    }
+
+    /// number of orders resting on the book
+    pub fn order_count(&self) -> usize {
+        self.order_lookup.len()
+    }
+
+    /// number of orders and their total quantity in front of the order in its price level
+    pub fn queue_position(&self, order_id: OrderId) -> Result<(usize, O::Qty), Error<O>> {
+        let (price, side) = self.lookup(order_id)?;
+        self.price_level(&price, &side)
+            .and_then(|i| i.queue_position(order_id))
+            .ok_or(OrderBookError::PriceLevelMissing { price, side })
+    }
+
+    /// iterates the price level with PriceQty, starting from the best price
+    pub fn iter_orders<'a>(
+        &'a self,
+        side: &Side,
+    ) -> impl Iterator<Item = PriceQty<O::Price, O::Qty>> + 'a {
+        self.iter_price_level(side).map(|i| i.price_qty())
+    }
+
+    /// iterates the price level, starting from the best price.
+    /// bids are iterated in descending and asks in ascending order of price.
+    pub fn iter_price_level<'a>(
+        &'a self,
+        side: &Side,
+    ) -> impl Iterator<Item = &'a PriceLevel<O>> + 'a {
+        self.ladder(side).iter()
+    }
+
+    /// resting market orders of the side
+    pub fn market_orders(&self, side: &Side) -> &PriceLevel<O> {
+        match side {
+            Side::Buy => &self.bid_market_orders,
+            Side::Sell => &self.ask_market_orders,
         }
     }
 
-    pub fn remove(&mut self, id: impl UniqueOrderId) -> Result<(), ()> {
-        let (price, side) = self.order_lookup.remove(&id.unique_order_id()).unwrap();
-        match self.mut_price_level(&price, &side) {
-            Ok(level) => level.remove(id.unique_order_id()),
-            Err(_idx) => return Err(()),
+    pub fn best_bid(&self) -> Option<PriceQty<O::Price, O::Qty>> {
+        self.bid_orders.best().map(|i| i.price_qty())
+    }
+
+    pub fn best_ask(&self) -> Option<PriceQty<O::Price, O::Qty>> {
+        self.ask_orders.best().map(|i| i.price_qty())
+    }
+
+    /// best ask - best bid
+    pub fn spread(&self) -> Option<O::Price> {
+        let (bid, ask) = self.touch()?;
+        Some(ask.price.price_min_if_market() - bid.price.price_min_if_market())
+    }
+
+    pub fn mid(&self) -> Option<f64> {
+        let (bid, ask) = self.touch()?;
+        let (bid_px, ask_px) = (
+            bid.price.price_min_if_market().to_f64()?,
+            ask.price.price_min_if_market().to_f64()?,
+        );
+        Some((bid_px + ask_px) / 2.)
+    }
+
+    /// mid price weighted by the quantity on the opposite side of the touch.
+    /// it leans towards the ask when the bid is heavier and vice versa.
+    pub fn microprice(&self) -> Option<f64> {
+        let (bid, ask) = self.touch()?;
+        let (bid_qty, ask_qty) = (bid.qty.to_f64()?, ask.qty.to_f64()?);
+        if bid_qty + ask_qty == 0. {
+            return None;
+        }
+        let (bid_px, ask_px) = (
+            bid.price.price_min_if_market().to_f64()?,
+            ask.price.price_min_if_market().to_f64()?,
+        );
+        Some((bid_px * ask_qty + ask_px * bid_qty) / (bid_qty + ask_qty))
+    }
+
+    /// the top `n` price levels of each side
+    pub fn depth(&self, n: usize) -> DepthSnapshot<O::Price, O::Qty> {
+        DepthSnapshot {
+            bids: self.iter_orders(&Side::Buy).take(n).collect(),
+            asks: self.iter_orders(&Side::Sell).take(n).collect(),
+        }
+    }
+
+    fn touch(&self) -> Option<(Level<O>, Level<O>)> {
+        self.best_bid().zip(self.best_ask())
+    }
+
+    /// adds the order to the book.
+    /// with `CrossingPolicy::Match` the order first trades against the opposite side
+    /// and only the residual is rested.
+    pub fn add(&mut self, order: O) -> Result<AddOutcome<O::Price, O::Qty>, Error<O>> {
+        self.insert(order, None)
+    }
+
+    /// same as `add`, but the residual is placed behind the first `rank` orders of its price level
+    /// instead of the back of the queue. for feeds that report the queue position of new orders.
+    pub fn add_at(
+        &mut self,
+        order: O,
+        rank: usize,
+    ) -> Result<AddOutcome<O::Price, O::Qty>, Error<O>> {
+        self.insert(order, Some(rank))
+    }
+
+    fn insert(
+        &mut self,
+        mut order: O,
+        rank: Option<usize>,
+    ) -> Result<AddOutcome<O::Price, O::Qty>, Error<O>> {
+        self.check_insert(&order, None)?;
+        let fills = match self.crossing_policy {
+            CrossingPolicy::Match => self.match_order(&mut order),
+            CrossingPolicy::Rest | CrossingPolicy::Reject => vec![],
         };
+        let resting_qty = order.qty();
+        if resting_qty > O::Qty::zero() {
+            self.rest(order, rank)?;
+        }
+        Ok(AddOutcome { fills, resting_qty })
+    }
+
+    /// every check `insert` does before it changes the book.
+    /// `replacing` is an order that is removed first, the new order may reuse its id.
+    fn check_insert(&self, order: &O, replacing: Option<OrderId>) -> Result<(), Error<O>> {
+        let order_id = order.unique_order_id();
+        if order.qty() < O::Qty::zero() {
+            return Err(OrderBookError::NegativeQty {
+                order_id,
+                qty: order.qty(),
+            });
+        }
+        if replacing != Some(order_id) && self.order_lookup.contains_key(&order_id) {
+            return Err(OrderBookError::DuplicateOrderId(order_id));
+        }
+        if self.crossing_policy == CrossingPolicy::Reject && self.crosses(order) {
+            return Err(OrderBookError::CrossedBook { order_id });
+        }
         Ok(())
     }
 
-    pub fn replace(&mut self, add: O, remove: impl UniqueOrderId) -> Result<(), ()> {
-        self.remove(remove)?;
-        self.add(add);
+    /// true if the order would trade against the best price level of the opposite side
+    fn crosses(&self, order: &O) -> bool {
+        let level = self.ladder(&order.side().opposite()).best();
+        match (level, order.price(), order.side()) {
+            (None, _, _) => false,
+            (Some(_), OrderPrice::Market, _) => true,
+            (Some(l), OrderPrice::Limit(p), Side::Buy) => l.price_min_if_market() <= p,
+            (Some(l), OrderPrice::Limit(p), Side::Sell) => l.price_min_if_market() >= p,
+        }
+    }
+
+    fn rest(&mut self, order: O, rank: Option<usize>) -> Result<(), Error<O>> {
+        let (order_id, price, side) = (order.unique_order_id(), order.price(), order.side());
+        match (self.mut_price_level(&price, &side), rank) {
+            (Some(level), Some(rank)) => level.insert_at(rank, order)?,
+            (Some(level), None) => level.add(order)?,
+            (None, _) => self
+                .mut_ladder(&side)
+                .insert(PriceLevel::new_with_order(order)),
+        }
+        self.order_lookup.insert(order_id, (price, side));
         Ok(())
     }
+
+    /// walks the opposite side from the best price and consumes resting quantity
+    /// until the order no longer crosses or is fully filled
+    fn match_order(&mut self, order: &mut O) -> Vec<Fill<O::Price, O::Qty>> {
+        let mut fills = vec![];
+        while order.qty() > O::Qty::zero() && self.crosses(order) {
+            // borrow the field, `order_lookup` is updated while the level is borrowed
+            let ladder = match order.side() {
+                Side::Buy => &mut self.ask_orders,
+                Side::Sell => &mut self.bid_orders,
+            };
+            let Some(level) = ladder.best_mut() else {
+                break;
+            };
+            let level_fills = level.fill(order.unique_order_id(), order.qty());
+            let filled = level_fills
+                .iter()
+                .fold(O::Qty::zero(), |sum, f| sum + f.qty);
+            order.set_qty(order.qty() - filled);
+            for fill in level_fills.iter() {
+                if !level.contains(fill.maker_id) {
+                    self.order_lookup.remove(&fill.maker_id);
+                }
+            }
+            if level.is_empty() {
+                let price = level.price_min_if_market();
+                ladder.remove(&price);
+            }
+            fills.extend(level_fills);
+        }
+        fills
+    }
+
+    /// removes the order from the book and returns it
+    pub fn remove(&mut self, id: impl UniqueOrderId) -> Result<O, Error<O>> {
+        let order_id = id.unique_order_id();
+        let (price, side) = self
+            .order_lookup
+            .remove(&order_id)
+            .ok_or(OrderBookError::UnknownOrderId(order_id))?;
+        let level = self
+            .mut_price_level(&price, &side)
+            .ok_or(OrderBookError::PriceLevelMissing { price, side })?;
+        let order = level
+            .remove(order_id)
+            .ok_or(OrderBookError::UnknownOrderId(order_id))?;
+        if level.is_empty() {
+            self.remove_price_level(&price, &side);
+        }
+        Ok(order)
+    }
+
+    /// drops the limit price level, market order stacks are kept
+    fn remove_price_level(&mut self, price: &OrderPrice<O::Price>, side: &Side) {
+        if let OrderPrice::Limit(price) = price {
+            self.mut_ladder(side).remove(price);
+        }
+    }
+
+    /// applies an execution reported by the exchange to the maker order
+    pub fn execute(
+        &mut self,
+        execution: &impl Execution<O::Price, O::Qty>,
+    ) -> Result<Trade<O::Price, O::Qty>, Error<O>> {
+        let maker_id = execution.maker_id();
+        let qty = execution.size();
+        if qty < O::Qty::zero() {
+            return Err(OrderBookError::NegativeQty {
+                order_id: maker_id,
+                qty,
+            });
+        }
+        let (price, side) = self.lookup(maker_id)?;
+        let level = self
+            .mut_price_level(&price, &side)
+            .ok_or(OrderBookError::PriceLevelMissing { price, side })?;
+        let remaining_qty = level.execute(maker_id, qty)?;
+        if remaining_qty.is_zero() {
+            if level.is_empty() {
+                self.remove_price_level(&price, &side);
+            }
+            self.order_lookup.remove(&maker_id);
+        }
+        Ok(Trade {
+            maker_id,
+            side,
+            price: execution.price().map(OrderPrice::Limit).unwrap_or(price),
+            qty,
+            remaining_qty,
+        })
+    }
+
+    /// sets the quantity of the order. the order loses its queue priority.
+    /// setting the quantity to 0 removes the order.
+    pub fn change_qty(
+        &mut self,
+        target_id: impl UniqueOrderId,
+        change_qty: O::Qty,
+    ) -> Result<(), Error<O>> {
+        let order_id = target_id.unique_order_id();
+        if change_qty < O::Qty::zero() {
+            return Err(OrderBookError::NegativeQty {
+                order_id,
+                qty: change_qty,
+            });
+        }
+        if change_qty.is_zero() {
+            return self.remove(order_id).map(|_| ());
+        }
+        let (price, side) = self.lookup(order_id)?;
+        let level = self
+            .mut_price_level(&price, &side)
+            .ok_or(OrderBookError::PriceLevelMissing { price, side })?;
+        let mut ord = level
+            .remove(order_id)
+            .ok_or(OrderBookError::UnknownOrderId(order_id))?;
+        ord.set_qty(change_qty);
+        level.add(ord)
+    }
+
+    /// removes `remove` and adds `add`. both orders have to be on the same side.
+    /// if `add` is refused, `remove` stays on the book.
+    pub fn replace(
+        &mut self,
+        add: O,
+        remove: impl UniqueOrderId,
+    ) -> Result<AddOutcome<O::Price, O::Qty>, Error<O>> {
+        let order_id = remove.unique_order_id();
+        let (_, side) = self.lookup(order_id)?;
+        if side != add.side() {
+            return Err(OrderBookError::SideMismatch {
+                order_id,
+                expected: add.side(),
+                found: side,
+            });
+        }
+        // the replaced order is on the same side, so it does not change whether `add` crosses
+        self.check_insert(&add, Some(order_id))?;
+        self.remove(order_id)?;
+        self.add(add)
+    }
+
+    /// removes every order from the book
+    pub fn clear(&mut self) {
+        self.ask_orders.clear();
+        self.bid_orders.clear();
+        self.order_lookup.clear();
+        self.ask_market_orders = Default::default();
+        self.bid_market_orders = Default::default();
+    }
+
+    fn lookup(&self, order_id: OrderId) -> Result<(OrderPrice<O::Price>, Side), Error<O>> {
+        self.order_lookup
+            .get(&order_id)
+            .copied()
+            .ok_or(OrderBookError::UnknownOrderId(order_id))
+    }
+}
+
+impl<O: Order + Clone> OrderBook<O> {
+    /// copies every resting order into a serializable snapshot, see `OrderBookSnapshot`
+    pub fn snapshot(&self) -> OrderBookSnapshot<O> {
+        let levels = |side| {
+            self.iter_price_level(&side)
+                .map(LevelSnapshot::from_price_level)
+                .collect()
+        };
+        OrderBookSnapshot {
+            order_book_id: self.order_book_id,
+            bids: levels(Side::Buy),
+            asks: levels(Side::Sell),
+            bid_market_orders: LevelSnapshot::from_price_level(&self.bid_market_orders).orders,
+            ask_market_orders: LevelSnapshot::from_price_level(&self.ask_market_orders).orders,
+        }
+    }
 }
 
 impl<O: Order> OrderBook<O> {
-    pub fn update(&mut self, msg: OrderBookUpdate) { 
-        
+    /// applies the update and reports the price levels it changed.
+    /// feed adapters only have to map their messages to `OrderBookUpdate`.
+    pub fn update<U: UniqueOrderId, E: Execution<O::Price, O::Qty>>(
+        &mut self,
+        msg: OrderBookUpdate<O, U, E>,
+    ) -> Result<UpdateOutcome<O::Price, O::Qty>, Error<O>> {
+        let mut outcome = UpdateOutcome::default();
+        match msg {
+            OrderBookUpdate::Add(order) => {
+                let (price, side) = (order.price(), order.side());
+                let added = self.add(order)?;
+                outcome
+                    .levels
+                    .extend(self.filled_levels(&added.fills, side));
+                if added.resting_qty > O::Qty::zero() {
+                    outcome.levels.push(self.level_change(price, side));
+                }
+                outcome.fills = added.fills;
+            }
+            OrderBookUpdate::Delete(id) => {
+                let order = self.remove(id)?;
+                outcome
+                    .levels
+                    .push(self.level_change(order.price(), order.side()));
+            }
+            OrderBookUpdate::ChangeQty(id, qty) => {
+                let (price, side) = self.lookup(id.unique_order_id())?;
+                self.change_qty(id, qty)?;
+                outcome.levels.push(self.level_change(price, side));
+            }
+            OrderBookUpdate::Update(order, id) => {
+                let (old_price, side) = self.lookup(id.unique_order_id())?;
+                let new_price = order.price();
+                let added = self.replace(order, id)?;
+                outcome.levels.push(self.level_change(old_price, side));
+                outcome
+                    .levels
+                    .extend(self.filled_levels(&added.fills, side));
+                if new_price != old_price && added.resting_qty > O::Qty::zero() {
+                    outcome.levels.push(self.level_change(new_price, side));
+                }
+                outcome.fills = added.fills;
+            }
+            OrderBookUpdate::Execution(execution) => {
+                let (price, side) = self.lookup(execution.maker_id())?;
+                outcome.trade = Some(self.execute(&execution)?);
+                outcome.levels.push(self.level_change(price, side));
+            }
+            OrderBookUpdate::Clear => {
+                for side in [Side::Buy, Side::Sell] {
+                    for level in self.iter_price_level(&side) {
+                        outcome.levels.push(LevelChange {
+                            side,
+                            price: level.price,
+                            qty: O::Qty::zero(),
+                        });
+                    }
+                    if !self.market_orders(&side).is_empty() {
+                        outcome.levels.push(LevelChange {
+                            side,
+                            price: OrderPrice::Market,
+                            qty: O::Qty::zero(),
+                        });
+                    }
+                }
+                self.clear();
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// levels on the opposite side of `taker_side` that were hit by the fills
+    fn filled_levels(
+        &self,
+        fills: &[Fill<O::Price, O::Qty>],
+        taker_side: Side,
+    ) -> Vec<LevelChange<O::Price, O::Qty>> {
+        let maker_side = taker_side.opposite();
+        let mut prices: Vec<O::Price> = fills.iter().map(|f| f.price).collect();
+        prices.dedup();
+        prices
+            .into_iter()
+            .map(|price| self.level_change(OrderPrice::Limit(price), maker_side))
+            .collect()
+    }
+
+    /// current state of the price level, `qty` is 0 if the level is gone
+    fn level_change(
+        &self,
+        price: OrderPrice<O::Price>,
+        side: Side,
+    ) -> LevelChange<O::Price, O::Qty> {
+        let qty = self
+            .price_level(&price, &side)
+            .map(|i| i.price_qty().qty())
+            .unwrap_or_else(O::Qty::zero);
+        LevelChange { side, price, qty }
     }
 }
 
-pub enum OrderBookUpdate<O: Order, U: UniqueOrderId> {
+/// Event that changes the book, see `OrderBook::update`
+pub enum OrderBookUpdate<O: Order, U: UniqueOrderId, E: Execution<O::Price, O::Qty>> {
     Add(O),
     Delete(U),
+    /// sets the quantity of the order `U`
+    ChangeQty(U, O::Qty),
+    /// replaces the order `U` with `O`, the new order goes to the back of the queue
     Update(O, U),
-    Execution,
-}
\ No newline at end of file
+    Execution(E),
+    Clear,
+}
+
+/// Price level after an update, `qty` is 0 when the level was removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelChange<P = i64, Q = i64>
+where
+    P: Copy,
+{
+    pub side: Side,
+    pub price: OrderPrice<P>,
+    pub qty: Q,
+}
+
+/// What `OrderBook::update` did to the book
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateOutcome<P = i64, Q = i64>
+where
+    P: Copy,
+{
+    /// every price level the update touched
+    pub levels: Vec<LevelChange<P, Q>>,
+    /// fills of an incoming order, only with `CrossingPolicy::Match`
+    pub fills: Vec<Fill<P, Q>>,
+    /// set for `OrderBookUpdate::Execution`
+    pub trade: Option<Trade<P, Q>>,
+}
+
+impl<P: Copy, Q> Default for UpdateOutcome<P, Q> {
+    fn default() -> Self {
+        Self {
+            levels: vec![],
+            fills: vec![],
+            trade: None,
+        }
+    }
+}
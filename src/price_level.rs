@@ -1,54 +1,152 @@
+use num_traits::Zero;
+
+use crate::queue::OrderQueue;
 use crate::*;
 
 pub struct PriceLevel<O: Order> {
-    order_stack: HashMap<OrderId, O>,
-    insertion_order: Vec<OrderId>,
-    pub(crate) price: i64,
-    qty: i64,
+    /// maker orders in time priority
+    queue: OrderQueue<O>,
+    pub(crate) price: OrderPrice<O::Price>,
+    qty: O::Qty,
+}
+
+impl<O: Order> Default for PriceLevel<O> {
+    fn default() -> Self {
+        Self {
+            queue: Default::default(),
+            price: Default::default(),
+            qty: O::Qty::zero(),
+        }
+    }
+}
+
+impl<O: Order> PriceLevel<O> {
+    pub fn price_min_if_market(&self) -> O::Price {
+        self.price.price_min_if_market()
+    }
+    pub fn is_market_order_stack(&self) -> bool {
+        matches!(self.price, OrderPrice::Market)
+    }
+    pub fn is_limit_order_stack(&self) -> bool {
+        matches!(self.price, OrderPrice::Limit(_))
+    }
 }
 
 impl<O: Order> PriceLevel<O> {
     pub fn new_with_order(order: O) -> Self {
-        Self {
+        let mut data = Self {
             price: order.price(),
-            qty: order.qty(),
-            insertion_order: {
-                vec![order.unique_order_id()]
-            },
-            order_stack: {
-                let mut map = HashMap::with_capacity(1000);
-                map.insert(order.unique_order_id(), order);
-                map
-            },
-        }
+            ..Default::default()
+        };
+        // an empty level has no order with the same id
+        let _ = data.add(order);
+        data
     }
-    pub fn add(&mut self, o: O) {
-        self.qty += o.qty();
-        self.insertion_order.push(o.unique_order_id());
-        self.order_stack.insert(o.unique_order_id(), o);
+    /// fails if an order with the same id is already in the level
+    pub fn add(&mut self, o: O) -> Result<(), OrderBookError<O::Price, O::Qty>> {
+        let (id, qty) = (o.unique_order_id(), o.qty());
+        self.queue
+            .push_back(id, o)
+            .map_err(|_| OrderBookError::DuplicateOrderId(id))?;
+        self.qty = self.qty + qty;
+        Ok(())
+    }
+    /// adds the order behind the first `rank` orders of the queue.
+    /// fails if an order with the same id is already in the level
+    pub fn insert_at(&mut self, rank: usize, o: O) -> Result<(), OrderBookError<O::Price, O::Qty>> {
+        let (id, qty) = (o.unique_order_id(), o.qty());
+        self.queue
+            .insert_at(rank, id, o)
+            .map_err(|_| OrderBookError::DuplicateOrderId(id))?;
+        self.qty = self.qty + qty;
+        Ok(())
     }
     pub fn remove(&mut self, id: OrderId) -> Option<O> {
-        match self.order_stack.remove(&id) {
-            Some(i) => {
-                self.qty -= i.qty();
-                Some(i)
-            }
-            None => None,
+        let order = self.queue.remove(id)?;
+        self.qty = self.qty - order.qty();
+        Some(order)
+    }
+    /// reduces the quantity of the order without changing its queue position.
+    /// returns the quantity that is left, the order is removed when nothing is left.
+    pub fn execute(
+        &mut self,
+        id: OrderId,
+        qty: O::Qty,
+    ) -> Result<O::Qty, OrderBookError<O::Price, O::Qty>> {
+        let order = self
+            .queue
+            .get_mut(id)
+            .ok_or(OrderBookError::UnknownOrderId(id))?;
+        if qty > order.qty() {
+            return Err(OrderBookError::Overfill {
+                order_id: id,
+                qty: order.qty(),
+                executed: qty,
+            });
+        }
+        let remaining = order.qty() - qty;
+        order.set_qty(remaining);
+        self.qty = self.qty - qty;
+        if remaining.is_zero() {
+            self.remove(id);
         }
+        Ok(remaining)
     }
-    pub fn price_qty(&self) -> PriceQty {
+    pub fn price_qty(&self) -> PriceQty<O::Price, O::Qty> {
         PriceQty {
             price: self.price,
             qty: self.qty,
         }
     }
+    /// consumes up to `qty` from the front of the queue and returns the fills.
+    /// maker orders that are fully filled are removed from the level.
+    pub fn fill(&mut self, taker_id: OrderId, mut qty: O::Qty) -> Vec<Fill<O::Price, O::Qty>> {
+        let price = self.price.price_min_if_market();
+        let mut fills = vec![];
+        while !qty.is_zero() {
+            let Some((maker_id, maker)) = self.queue.front_mut() else {
+                break;
+            };
+            let traded = if maker.qty() < qty { maker.qty() } else { qty };
+            maker.set_qty(maker.qty() - traded);
+            self.qty = self.qty - traded;
+            qty = qty - traded;
+            fills.push(Fill {
+                maker_id,
+                taker_id,
+                price,
+                qty: traded,
+            });
+            if maker.qty().is_zero() {
+                self.queue.pop_front();
+            }
+        }
+        fills
+    }
+    pub fn order(&self, id: OrderId) -> Option<&O> {
+        self.queue.get(id)
+    }
+    pub fn contains(&self, id: OrderId) -> bool {
+        self.queue.contains(id)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+    /// number of maker orders in the queue
+    pub fn order_count(&self) -> usize {
+        self.queue.len()
+    }
+    /// number of orders and their total quantity in front of the order.
+    /// walks the queue from the order to the front.
+    /// `None` if the order is not in this price level.
+    pub fn queue_position(&self, id: OrderId) -> Option<(usize, O::Qty)> {
+        let ahead = self.queue.iter_ahead(id)?;
+        Some(ahead.fold((0, O::Qty::zero()), |(orders, qty), order| {
+            (orders + 1, qty + order.qty())
+        }))
+    }
     /// iterate maker orders
     pub fn iter_orders(&self) -> impl Iterator<Item = (&OrderId, &O)> {
-        self.insertion_order.iter().filter_map(|id| {
-            match self.order_stack.get(id) {
-                Some(ord) => Some((id, ord)),
-                None => None
-            }
-        })
+        self.queue.iter()
     }
 }
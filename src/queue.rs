@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use market_datatypes::OrderId;
+use slab::Slab;
+
+struct Node<T> {
+    id: OrderId,
+    item: T,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+/// FIFO of orders that supports removing any order by id
+///
+/// doubly linked list stored in a slab, so add, cancel and front-pop are O(1)
+/// and the slots of cancelled orders are reused.
+pub(crate) struct OrderQueue<T> {
+    nodes: Slab<Node<T>>,
+    index: HashMap<OrderId, usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+}
+
+impl<T> Default for OrderQueue<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Slab::new(),
+            index: HashMap::new(),
+            head: None,
+            tail: None,
+        }
+    }
+}
+
+impl<T> OrderQueue<T> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, id: OrderId) -> bool {
+        self.index.contains_key(&id)
+    }
+
+    /// adds the order to the back of the queue.
+    /// returns the order back if an order with the same id is already queued.
+    pub fn push_back(&mut self, id: OrderId, item: T) -> Result<(), T> {
+        if self.contains(id) {
+            return Err(item);
+        }
+        let key = self.nodes.insert(Node {
+            id,
+            item,
+            prev: self.tail,
+            next: None,
+        });
+        match self.tail {
+            Some(tail) => self.nodes[tail].next = Some(key),
+            None => self.head = Some(key),
+        }
+        self.tail = Some(key);
+        self.index.insert(id, key);
+        Ok(())
+    }
+
+    /// inserts the order so that `rank` orders are in front of it,
+    /// orders with a rank past the back of the queue are added to the back.
+    /// returns the order back if an order with the same id is already queued.
+    pub fn insert_at(&mut self, rank: usize, id: OrderId, item: T) -> Result<(), T> {
+        if self.contains(id) {
+            return Err(item);
+        }
+        let mut next = self.head;
+        for _ in 0..rank {
+            match next {
+                Some(key) => next = self.nodes[key].next,
+                None => break,
+            }
+        }
+        let Some(next) = next else {
+            return self.push_back(id, item);
+        };
+        let prev = self.nodes[next].prev;
+        let key = self.nodes.insert(Node {
+            id,
+            item,
+            prev,
+            next: Some(next),
+        });
+        self.nodes[next].prev = Some(key);
+        match prev {
+            Some(prev) => self.nodes[prev].next = Some(key),
+            None => self.head = Some(key),
+        }
+        self.index.insert(id, key);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: OrderId) -> Option<T> {
+        let key = self.index.remove(&id)?;
+        let node = self.nodes.remove(key);
+        match node.prev {
+            Some(prev) => self.nodes[prev].next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => self.nodes[next].prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        Some(node.item)
+    }
+
+    pub fn front_mut(&mut self) -> Option<(OrderId, &mut T)> {
+        let node = &mut self.nodes[self.head?];
+        Some((node.id, &mut node.item))
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let id = self.nodes[self.head?].id;
+        self.remove(id)
+    }
+
+    pub fn get(&self, id: OrderId) -> Option<&T> {
+        let key = *self.index.get(&id)?;
+        Some(&self.nodes[key].item)
+    }
+
+    pub fn get_mut(&mut self, id: OrderId) -> Option<&mut T> {
+        let key = *self.index.get(&id)?;
+        Some(&mut self.nodes[key].item)
+    }
+
+    /// iterates in queue order
+    pub fn iter(&self) -> impl Iterator<Item = (&OrderId, &T)> {
+        let mut cursor = self.head;
+        std::iter::from_fn(move || {
+            let node = &self.nodes[cursor?];
+            cursor = node.next;
+            Some((&node.id, &node.item))
+        })
+    }
+
+    /// iterates the orders in front of `id`, starting from the closest one.
+    /// `None` if the order is not in the queue.
+    pub fn iter_ahead(&self, id: OrderId) -> Option<impl Iterator<Item = &T>> {
+        let mut cursor = self.nodes[*self.index.get(&id)?].prev;
+        Some(std::iter::from_fn(move || {
+            let node = &self.nodes[cursor?];
+            cursor = node.prev;
+            Some(&node.item)
+        }))
+    }
+}
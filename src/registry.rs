@@ -0,0 +1,159 @@
+use crate::orderbook::Error;
+use crate::*;
+
+/// Order books of every instrument of a feed
+///
+/// books are created on the first update for their `order_book_id`.
+/// the registry remembers which book each resting order is on,
+/// so messages that only carry the order id can be routed as well.
+pub struct OrderBookRegistry<O: Order = MakerOrder> {
+    books: HashMap<u64, OrderBook<O>>,
+    /// order id -> order_book_id of the book the order is resting on
+    order_index: HashMap<OrderId, u64>,
+    /// crossing policy of books that are created by the registry
+    crossing_policy: CrossingPolicy,
+}
+
+impl<O: Order> Default for OrderBookRegistry<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Order> OrderBookRegistry<O> {
+    pub fn new() -> Self {
+        Self {
+            books: HashMap::new(),
+            order_index: HashMap::new(),
+            crossing_policy: CrossingPolicy::default(),
+        }
+    }
+
+    /// creates a registry whose books use `policy`
+    pub fn with_crossing_policy(policy: CrossingPolicy) -> Self {
+        let mut data = Self::new();
+        data.crossing_policy = policy;
+        data
+    }
+
+    pub fn get(&self, order_book_id: u64) -> Option<&OrderBook<O>> {
+        self.books.get(&order_book_id)
+    }
+
+    /// number of books
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// iterates every book in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &OrderBook<O>)> {
+        self.books.iter().map(|(id, book)| (*id, book))
+    }
+
+    /// the resting order with the id, from whichever book it is on
+    pub fn order(&self, order_id: OrderId) -> Option<&O> {
+        self.books
+            .get(self.order_index.get(&order_id)?)?
+            .order(order_id)
+    }
+
+    /// id of the book the order is resting on
+    pub fn order_book_id_of(&self, order_id: OrderId) -> Option<u64> {
+        self.order_index.get(&order_id).copied()
+    }
+
+    /// applies the update to the book of `order_book_id`, creating the book if needed
+    pub fn update<U: UniqueOrderId, E: Execution<O::Price, O::Qty>>(
+        &mut self,
+        order_book_id: u64,
+        msg: OrderBookUpdate<O, U, E>,
+    ) -> Result<UpdateOutcome<O::Price, O::Qty>, Error<O>> {
+        let touched = match &msg {
+            OrderBookUpdate::Add(order) => vec![order.unique_order_id()],
+            OrderBookUpdate::Delete(id) | OrderBookUpdate::ChangeQty(id, _) => {
+                vec![id.unique_order_id()]
+            }
+            OrderBookUpdate::Update(order, id) => {
+                vec![order.unique_order_id(), id.unique_order_id()]
+            }
+            OrderBookUpdate::Execution(execution) => vec![execution.maker_id()],
+            OrderBookUpdate::Clear => vec![],
+        };
+        // order ids have to be unique across the books
+        if let OrderBookUpdate::Add(order) | OrderBookUpdate::Update(order, _) = &msg {
+            let order_id = order.unique_order_id();
+            if self
+                .order_book_id_of(order_id)
+                .is_some_and(|id| id != order_book_id)
+            {
+                return Err(OrderBookError::DuplicateOrderId(order_id));
+            }
+        }
+        let is_clear = matches!(msg, OrderBookUpdate::Clear);
+        let crossing_policy = self.crossing_policy;
+        let book = self.books.entry(order_book_id).or_insert_with(|| {
+            let mut book = OrderBook::new(order_book_id);
+            book.set_crossing_policy(crossing_policy);
+            book
+        });
+        let outcome = book.update(msg)?;
+
+        if is_clear {
+            self.order_index.retain(|_, id| *id != order_book_id);
+        }
+        let makers = outcome.fills.iter().map(|fill| fill.maker_id);
+        for order_id in touched.into_iter().chain(makers) {
+            if book.contains_order(order_id) {
+                self.order_index.insert(order_id, order_book_id);
+            } else if self.order_index.get(&order_id) == Some(&order_book_id) {
+                self.order_index.remove(&order_id);
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// removes the order from whichever book it is resting on
+    pub fn remove(&mut self, id: impl UniqueOrderId) -> Result<O, Error<O>> {
+        let order_id = id.unique_order_id();
+        let book = self.book_of(order_id)?;
+        let order = book.remove(order_id)?;
+        self.order_index.remove(&order_id);
+        Ok(order)
+    }
+
+    /// sets the quantity of the order on whichever book it is resting on, 0 removes the order
+    pub fn change_qty(&mut self, id: impl UniqueOrderId, qty: O::Qty) -> Result<(), Error<O>> {
+        let order_id = id.unique_order_id();
+        let book = self.book_of(order_id)?;
+        book.change_qty(order_id, qty)?;
+        if !book.contains_order(order_id) {
+            self.order_index.remove(&order_id);
+        }
+        Ok(())
+    }
+
+    /// applies the execution to whichever book the maker order is resting on
+    pub fn execute(
+        &mut self,
+        execution: &impl Execution<O::Price, O::Qty>,
+    ) -> Result<Trade<O::Price, O::Qty>, Error<O>> {
+        let order_id = execution.maker_id();
+        let book = self.book_of(order_id)?;
+        let trade = book.execute(execution)?;
+        if !book.contains_order(order_id) {
+            self.order_index.remove(&order_id);
+        }
+        Ok(trade)
+    }
+
+    fn book_of(&mut self, order_id: OrderId) -> Result<&mut OrderBook<O>, Error<O>> {
+        self.order_index
+            .get(&order_id)
+            .and_then(|id| self.books.get_mut(id))
+            .ok_or(OrderBookError::UnknownOrderId(order_id))
+    }
+}
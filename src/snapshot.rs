@@ -0,0 +1,49 @@
+use market_datatypes::OrderPrice;
+
+use crate::{MakerOrder, Order, PriceLevel};
+
+/// Owned copy of an `OrderBook`, e.g. for dumping book states to JSON and diffing them
+///
+/// price levels are sorted best price first and orders are in queue order
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "O: serde::Serialize, O::Price: serde::Serialize, O::Qty: serde::Serialize",
+        deserialize = "O: serde::Deserialize<'de>, O::Price: serde::Deserialize<'de>, O::Qty: serde::Deserialize<'de>"
+    ))
+)]
+pub struct OrderBookSnapshot<O: Order = MakerOrder> {
+    pub order_book_id: u64,
+    pub bids: Vec<LevelSnapshot<O>>,
+    pub asks: Vec<LevelSnapshot<O>>,
+    pub bid_market_orders: Vec<O>,
+    pub ask_market_orders: Vec<O>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "O: serde::Serialize, O::Price: serde::Serialize, O::Qty: serde::Serialize",
+        deserialize = "O: serde::Deserialize<'de>, O::Price: serde::Deserialize<'de>, O::Qty: serde::Deserialize<'de>"
+    ))
+)]
+pub struct LevelSnapshot<O: Order = MakerOrder> {
+    pub price: OrderPrice<O::Price>,
+    pub qty: O::Qty,
+    pub orders: Vec<O>,
+}
+
+impl<O: Order + Clone> LevelSnapshot<O> {
+    pub fn from_price_level(level: &PriceLevel<O>) -> Self {
+        let price_qty = level.price_qty();
+        Self {
+            price: price_qty.price(),
+            qty: price_qty.qty(),
+            orders: level.iter_orders().map(|(_, o)| o.clone()).collect(),
+        }
+    }
+}
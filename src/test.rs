@@ -0,0 +1,696 @@
+use market_datatypes::{OrderPrice, Side};
+
+use crate::{
+    CrossingPolicy, Fill, LevelChange, MakerOrder, OrderBook, OrderBookError, OrderBookRegistry,
+    OrderBookUpdate, PriceLevel,
+};
+
+fn order(id: u64, side: Side, price: i64, qty: i64) -> MakerOrder {
+    MakerOrder {
+        id,
+        price: OrderPrice::Limit(price),
+        qty,
+        side,
+    }
+}
+
+/// order ids of every price level of the side, from the best price
+fn queues(book: &OrderBook, side: Side) -> Vec<Vec<u64>> {
+    book.iter_price_level(&side)
+        .map(|level| level.iter_orders().map(|(id, _)| *id).collect())
+        .collect()
+}
+
+#[test]
+fn limit_orders_join_the_level_of_their_own_side() {
+    let mut book = OrderBook::new(0);
+    book.add(order(1, Side::Buy, 100, 5)).unwrap();
+    book.add(order(2, Side::Sell, 101, 3)).unwrap();
+    book.add(order(3, Side::Buy, 100, 2)).unwrap();
+    book.add(order(4, Side::Sell, 101, 4)).unwrap();
+    assert_eq!(queues(&book, Side::Buy), vec![vec![1, 3]]);
+    assert_eq!(queues(&book, Side::Sell), vec![vec![2, 4]]);
+}
+
+#[test]
+fn rest_policy_does_not_match() {
+    let mut book = OrderBook::new(0);
+    assert_eq!(book.crossing_policy(), CrossingPolicy::Rest);
+    book.add(order(1, Side::Sell, 100, 5)).unwrap();
+    let outcome = book.add(order(2, Side::Buy, 101, 5)).unwrap();
+    assert!(outcome.fills.is_empty());
+    assert_eq!(outcome.resting_qty, 5);
+    assert_eq!(book.iter_price_level(&Side::Buy).count(), 1);
+    assert_eq!(book.iter_price_level(&Side::Sell).count(), 1);
+}
+
+#[test]
+fn match_in_price_time_priority() {
+    let mut book = OrderBook::new_matching(0);
+    book.add(order(1, Side::Sell, 101, 3)).unwrap();
+    book.add(order(2, Side::Sell, 100, 2)).unwrap();
+    book.add(order(3, Side::Sell, 100, 4)).unwrap();
+
+    let outcome = book.add(order(10, Side::Buy, 101, 7)).unwrap();
+    assert_eq!(
+        outcome.fills,
+        vec![
+            Fill {
+                maker_id: 2,
+                taker_id: 10,
+                price: 100,
+                qty: 2
+            },
+            Fill {
+                maker_id: 3,
+                taker_id: 10,
+                price: 100,
+                qty: 4
+            },
+            Fill {
+                maker_id: 1,
+                taker_id: 10,
+                price: 101,
+                qty: 1
+            },
+        ]
+    );
+    assert_eq!(outcome.resting_qty, 0);
+
+    // the price level at 100 is gone and order 1 has 2 left
+    let asks: Vec<_> = book.iter_price_level(&Side::Sell).collect();
+    assert_eq!(asks.len(), 1);
+    let (_, remaining) = asks[0].iter_orders().next().unwrap();
+    assert_eq!(remaining.id, 1);
+    assert_eq!(remaining.qty, 2);
+}
+
+#[test]
+fn match_rests_residual() {
+    let mut book = OrderBook::new_matching(0);
+    book.add(order(1, Side::Buy, 99, 3)).unwrap();
+    book.add(order(2, Side::Buy, 98, 3)).unwrap();
+
+    let outcome = book.add(order(10, Side::Sell, 99, 5)).unwrap();
+    assert_eq!(outcome.filled_qty(), 3);
+    assert_eq!(outcome.resting_qty, 2);
+
+    let bids: Vec<_> = book.iter_price_level(&Side::Buy).collect();
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids[0].price_min_if_market(), 98);
+    let asks: Vec<_> = book.iter_price_level(&Side::Sell).collect();
+    assert_eq!(asks.len(), 1);
+    assert_eq!(asks[0].price_min_if_market(), 99);
+}
+
+#[test]
+fn unknown_order_id_is_an_error() {
+    let mut book: OrderBook = OrderBook::new(0);
+    assert_eq!(
+        book.remove(42).err(),
+        Some(OrderBookError::UnknownOrderId(42))
+    );
+    assert_eq!(
+        book.change_qty(42, 1),
+        Err(OrderBookError::UnknownOrderId(42))
+    );
+}
+
+#[test]
+fn negative_qty_is_an_error() {
+    let mut book = OrderBook::new(0);
+    assert_eq!(
+        book.add(order(1, Side::Buy, 100, -1)),
+        Err(OrderBookError::NegativeQty {
+            order_id: 1,
+            qty: -1
+        })
+    );
+}
+
+#[test]
+fn reject_policy_refuses_crossing_orders() {
+    let mut book = OrderBook::new(0);
+    book.set_crossing_policy(CrossingPolicy::Reject);
+    book.add(order(1, Side::Sell, 100, 5)).unwrap();
+    book.add(order(2, Side::Buy, 99, 5)).unwrap();
+    assert_eq!(
+        book.add(order(3, Side::Buy, 100, 5)),
+        Err(OrderBookError::CrossedBook { order_id: 3 })
+    );
+}
+
+#[test]
+fn rejected_replace_keeps_the_old_order() {
+    let mut book = OrderBook::new(0);
+    book.set_crossing_policy(CrossingPolicy::Reject);
+    book.add(order(1, Side::Sell, 100, 5)).unwrap();
+    book.add(order(2, Side::Buy, 98, 5)).unwrap();
+    book.add(order(3, Side::Buy, 98, 4)).unwrap();
+    book.add(order(4, Side::Buy, 97, 1)).unwrap();
+    let queue = |book: &OrderBook| -> Vec<_> {
+        let level = book.iter_price_level(&Side::Buy).next().unwrap();
+        level.iter_orders().map(|(id, o)| (*id, o.qty)).collect()
+    };
+
+    assert_eq!(
+        book.replace(order(5, Side::Buy, 100, 5), 2),
+        Err(OrderBookError::CrossedBook { order_id: 5 })
+    );
+    assert_eq!(
+        book.replace(order(4, Side::Buy, 99, 5), 2),
+        Err(OrderBookError::DuplicateOrderId(4))
+    );
+    assert_eq!(
+        book.replace(order(5, Side::Buy, 99, -1), 2),
+        Err(OrderBookError::NegativeQty {
+            order_id: 5,
+            qty: -1
+        })
+    );
+    assert!(book.contains_order(2));
+    assert_eq!(book.order_count(), 4);
+    assert_eq!(queue(&book), vec![(2, 5), (3, 4)]);
+
+    // the replacement may keep the id of the order it replaces
+    book.replace(order(2, Side::Buy, 99, 2), 2).unwrap();
+    assert_eq!(queue(&book), vec![(2, 2)]);
+    assert_eq!(book.order_count(), 4);
+}
+
+#[test]
+fn lookup_follows_add_remove_and_modify() {
+    let mut book = OrderBook::new(0);
+    book.add(order(1, Side::Buy, 100, 5)).unwrap();
+    book.add(order(2, Side::Buy, 100, 5)).unwrap();
+    assert!(book.contains_order(1));
+    assert_eq!(
+        book.add(order(1, Side::Buy, 101, 1)),
+        Err(OrderBookError::DuplicateOrderId(1))
+    );
+
+    book.change_qty(1, 3).unwrap();
+    let level = book.iter_price_level(&Side::Buy).next().unwrap();
+    let queue: Vec<_> = level.iter_orders().map(|(id, o)| (*id, o.qty)).collect();
+    assert_eq!(queue, vec![(2, 5), (1, 3)]);
+
+    book.remove(1).unwrap();
+    book.change_qty(2, 0).unwrap();
+    assert_eq!(book.order_count(), 0);
+    assert_eq!(book.iter_price_level(&Side::Buy).count(), 0);
+}
+
+#[test]
+fn top_of_book() {
+    let mut book = OrderBook::new(0);
+    assert_eq!(book.best_bid(), None);
+    assert_eq!(book.spread(), None);
+    book.add(order(1, Side::Buy, 98, 1)).unwrap();
+    book.add(order(2, Side::Buy, 99, 1)).unwrap();
+    book.add(order(3, Side::Buy, 99, 2)).unwrap();
+    book.add(order(4, Side::Sell, 101, 1)).unwrap();
+    book.add(order(5, Side::Sell, 102, 4)).unwrap();
+
+    assert_eq!(
+        book.best_bid().map(|i| (i.price, i.qty)),
+        Some((OrderPrice::Limit(99), 3))
+    );
+    assert_eq!(
+        book.best_ask().map(|i| (i.price, i.qty)),
+        Some((OrderPrice::Limit(101), 1))
+    );
+    assert_eq!(book.spread(), Some(2));
+    assert_eq!(book.mid(), Some(100.));
+    // 3 on the bid vs 1 on the ask pushes the microprice towards the ask
+    assert_eq!(book.microprice(), Some(100.5));
+
+    let depth = book.depth(1);
+    assert_eq!(depth.bids.len(), 1);
+    assert_eq!(depth.asks.len(), 1);
+    let depth = book.depth(5);
+    let bids: Vec<_> = depth.bids.iter().map(|i| i.price).collect();
+    let asks: Vec<_> = depth.asks.iter().map(|i| i.price).collect();
+    assert_eq!(bids, vec![OrderPrice::Limit(99), OrderPrice::Limit(98)]);
+    assert_eq!(asks, vec![OrderPrice::Limit(101), OrderPrice::Limit(102)]);
+}
+
+mod prop {
+    use std::collections::BTreeMap;
+
+    use proptest::prelude::*;
+
+    use super::order;
+    use crate::*;
+
+    #[derive(Debug, Clone)]
+    enum Event {
+        Add { side: Side, price: i64, qty: i64 },
+        Cancel(usize),
+        Modify(usize, i64),
+    }
+
+    fn event() -> impl Strategy<Value = Event> {
+        prop_oneof![
+            3 => (any::<bool>(), 95..105i64, 1..10i64).prop_map(|(buy, price, qty)| Event::Add {
+                side: if buy { Side::Buy } else { Side::Sell },
+                price,
+                qty
+            }),
+            // far away prices move the window of the ladder around
+            1 => (any::<bool>(), -3000..3000i64, 1..10i64).prop_map(|(buy, price, qty)| Event::Add {
+                side: if buy { Side::Buy } else { Side::Sell },
+                price,
+                qty
+            }),
+            1 => any::<usize>().prop_map(Event::Cancel),
+            1 => (any::<usize>(), 0..10i64).prop_map(|(i, qty)| Event::Modify(i, qty)),
+        ]
+    }
+
+    /// naive book that keeps every order in arrival order
+    #[derive(Default)]
+    struct ReferenceBook {
+        orders: Vec<(u64, Side, i64, i64)>,
+    }
+
+    impl ReferenceBook {
+        fn depth(&self, side: Side) -> Vec<(i64, i64)> {
+            let mut levels = BTreeMap::new();
+            for (_, s, price, qty) in self.orders.iter() {
+                if *s == side {
+                    *levels.entry(*price).or_insert(0) += qty;
+                }
+            }
+            levels.into_iter().collect()
+        }
+
+        /// matches like `CrossingPolicy::Match`, returns (maker_id, price, qty) for every fill
+        fn match_order(
+            &mut self,
+            id: u64,
+            side: Side,
+            price: i64,
+            mut qty: i64,
+        ) -> Vec<(u64, i64, i64)> {
+            let mut fills = vec![];
+            while qty > 0 {
+                let best = self
+                    .orders
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| o.1 != side)
+                    .filter(|(_, o)| {
+                        if side.is_buy() {
+                            o.2 <= price
+                        } else {
+                            o.2 >= price
+                        }
+                    })
+                    // arrival order breaks ties, `min_by_key` keeps the first minimum
+                    .min_by_key(|(_, o)| if side.is_buy() { o.2 } else { -o.2 })
+                    .map(|(idx, _)| idx);
+                let Some(idx) = best else { break };
+                let maker = &mut self.orders[idx];
+                let traded = maker.3.min(qty);
+                maker.3 -= traded;
+                qty -= traded;
+                fills.push((maker.0, maker.2, traded));
+                if maker.3 == 0 {
+                    self.orders.remove(idx);
+                }
+            }
+            if qty > 0 {
+                self.orders.push((id, side, price, qty));
+            }
+            fills
+        }
+    }
+
+    fn book_depth(book: &OrderBook, side: Side) -> Vec<(i64, i64)> {
+        book.iter_price_level(&side)
+            .map(|level| (level.price_min_if_market(), level.price_qty().qty()))
+            .collect()
+    }
+
+    fn assert_same(book: &OrderBook, reference: &ReferenceBook) {
+        let mut bids = reference.depth(Side::Buy);
+        bids.reverse();
+        assert_eq!(book_depth(book, Side::Buy), bids);
+        assert_eq!(book_depth(book, Side::Sell), reference.depth(Side::Sell));
+        assert_eq!(book.order_count(), reference.orders.len());
+        for (idx, (id, side, price, _)) in reference.orders.iter().enumerate() {
+            let ahead = reference.orders[..idx]
+                .iter()
+                .filter(|o| o.1 == *side && o.2 == *price);
+            let expected = (ahead.clone().count(), ahead.map(|o| o.3).sum());
+            assert_eq!(book.queue_position(*id), Ok(expected));
+        }
+    }
+
+    /// picks a live order for `idx`, or an id that was never used one time out of eight
+    fn target(reference: &ReferenceBook, idx: usize) -> u64 {
+        if reference.orders.is_empty() || idx % 8 == 7 {
+            u64::MAX
+        } else {
+            reference.orders[idx % reference.orders.len()].0
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn rest_policy_matches_reference(events in prop::collection::vec(event(), 1..200)) {
+            let mut book = OrderBook::new(0);
+            let mut reference = ReferenceBook::default();
+            for (id, event) in events.into_iter().enumerate() {
+                let id = id as u64;
+                match event {
+                    Event::Add { side, price, qty } => {
+                        book.add(order(id, side, price, qty)).unwrap();
+                        reference.orders.push((id, side, price, qty));
+                    }
+                    Event::Cancel(idx) => {
+                        let target = target(&reference, idx);
+                        let result = book.remove(target);
+                        match reference.orders.iter().position(|o| o.0 == target) {
+                            Some(pos) => {
+                                prop_assert!(result.is_ok());
+                                reference.orders.remove(pos);
+                            }
+                            None => prop_assert_eq!(result.err(), Some(OrderBookError::UnknownOrderId(target))),
+                        }
+                    }
+                    Event::Modify(idx, qty) => {
+                        let target = target(&reference, idx);
+                        let result = book.change_qty(target, qty);
+                        match reference.orders.iter().position(|o| o.0 == target) {
+                            Some(pos) => {
+                                prop_assert!(result.is_ok());
+                                let mut modified = reference.orders.remove(pos);
+                                modified.3 = qty;
+                                if qty > 0 {
+                                    reference.orders.push(modified);
+                                }
+                            }
+                            None => prop_assert_eq!(result, Err(OrderBookError::UnknownOrderId(target))),
+                        }
+                    }
+                }
+                assert_same(&book, &reference);
+            }
+        }
+
+        #[test]
+        fn match_policy_matches_reference(events in prop::collection::vec(event(), 1..200)) {
+            let mut book = OrderBook::new_matching(0);
+            let mut reference = ReferenceBook::default();
+            for (id, event) in events.into_iter().enumerate() {
+                let id = id as u64;
+                match event {
+                    Event::Add { side, price, qty } => {
+                        let outcome = book.add(order(id, side, price, qty)).unwrap();
+                        let fills: Vec<_> = outcome.fills.iter().map(|f| (f.maker_id, f.price, f.qty)).collect();
+                        prop_assert_eq!(fills, reference.match_order(id, side, price, qty));
+                    }
+                    Event::Cancel(idx) => {
+                        let target = target(&reference, idx);
+                        if let Some(pos) = reference.orders.iter().position(|o| o.0 == target) {
+                            reference.orders.remove(pos);
+                            prop_assert!(book.remove(target).is_ok());
+                        }
+                    }
+                    // modifying never crosses because the price stays the same
+                    Event::Modify(..) => (),
+                }
+                assert_same(&book, &reference);
+                if let (Some(bid), Some(ask)) = (book.iter_price_level(&Side::Buy).next(), book.iter_price_level(&Side::Sell).next()) {
+                    prop_assert!(bid.price_min_if_market() < ask.price_min_if_market());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_round_trips_through_json() {
+    let mut book = OrderBook::new(7);
+    book.add(order(1, Side::Buy, 99, 1)).unwrap();
+    book.add(order(2, Side::Buy, 99, 2)).unwrap();
+    book.add(order(3, Side::Sell, 101, 4)).unwrap();
+
+    let snapshot = book.snapshot();
+    assert_eq!(snapshot.order_book_id, 7);
+    assert_eq!(snapshot.bids[0].qty, 3);
+    assert_eq!(snapshot.bids[0].orders.len(), 2);
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let parsed: crate::OrderBookSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, snapshot);
+}
+
+struct Exec(u64, i64);
+
+impl crate::Execution for Exec {
+    fn maker_id(&self) -> u64 {
+        self.0
+    }
+    fn size(&self) -> i64 {
+        self.1
+    }
+}
+
+#[test]
+fn execution_reduces_and_removes_maker() {
+    let mut book = OrderBook::new(0);
+    book.add(order(1, Side::Sell, 101, 5)).unwrap();
+    book.add(order(2, Side::Sell, 101, 1)).unwrap();
+
+    let trade = book.execute(&Exec(1, 2)).unwrap();
+    assert_eq!(trade.price, OrderPrice::Limit(101));
+    assert_eq!(trade.side, Side::Sell);
+    assert_eq!(trade.remaining_qty, 3);
+    // a partial execution keeps the queue position
+    let level = book.iter_price_level(&Side::Sell).next().unwrap();
+    let queue: Vec<_> = level.iter_orders().map(|(id, o)| (*id, o.qty)).collect();
+    assert_eq!(queue, vec![(1, 3), (2, 1)]);
+    assert_eq!(book.best_ask().unwrap().qty(), 4);
+
+    assert_eq!(
+        book.execute(&Exec(1, 4)),
+        Err(OrderBookError::Overfill {
+            order_id: 1,
+            qty: 3,
+            executed: 4
+        })
+    );
+    book.execute(&Exec(1, 3)).unwrap();
+    book.execute(&Exec(2, 1)).unwrap();
+    assert_eq!(book.order_count(), 0);
+    assert_eq!(book.best_ask(), None);
+    assert_eq!(
+        book.execute(&Exec(2, 1)),
+        Err(OrderBookError::UnknownOrderId(2))
+    );
+}
+
+#[test]
+fn update_reports_changed_levels() {
+    type Update = OrderBookUpdate<MakerOrder, u64, Exec>;
+    let level = |side, price, qty| LevelChange {
+        side,
+        price: OrderPrice::Limit(price),
+        qty,
+    };
+    let mut book = OrderBook::new(0);
+
+    let outcome = book
+        .update(Update::Add(order(1, Side::Buy, 100, 5)))
+        .unwrap();
+    assert_eq!(outcome.levels, vec![level(Side::Buy, 100, 5)]);
+    book.update(Update::Add(order(2, Side::Buy, 100, 2)))
+        .unwrap();
+
+    let outcome = book.update(Update::ChangeQty(1, 3)).unwrap();
+    assert_eq!(outcome.levels, vec![level(Side::Buy, 100, 5)]);
+
+    let outcome = book
+        .update(Update::Update(order(2, Side::Buy, 99, 2), 2))
+        .unwrap();
+    assert_eq!(
+        outcome.levels,
+        vec![level(Side::Buy, 100, 3), level(Side::Buy, 99, 2)]
+    );
+
+    let outcome = book.update(Update::Execution(Exec(1, 3))).unwrap();
+    assert_eq!(outcome.trade.unwrap().remaining_qty, 0);
+    assert_eq!(outcome.levels, vec![level(Side::Buy, 100, 0)]);
+
+    let outcome = book.update(Update::Delete(2)).unwrap();
+    assert_eq!(outcome.levels, vec![level(Side::Buy, 99, 0)]);
+    assert!(book
+        .update(Update::Delete(2))
+        .unwrap_err()
+        .is_unknown_order_id());
+
+    book.update(Update::Add(order(3, Side::Sell, 101, 1)))
+        .unwrap();
+    let outcome = book.update(Update::Clear).unwrap();
+    assert_eq!(outcome.levels, vec![level(Side::Sell, 101, 0)]);
+    assert_eq!(book.order_count(), 0);
+}
+
+#[test]
+fn fractional_quantities() {
+    let order = |id, side, price, qty| MakerOrder::<u64, f64> {
+        id,
+        price: OrderPrice::Limit(price),
+        qty,
+        side,
+    };
+    let mut book = OrderBook::new_matching(0);
+    book.add(order(1, Side::Sell, 30_001, 0.25)).unwrap();
+    book.add(order(2, Side::Sell, 30_002, 1.5)).unwrap();
+    book.add(order(3, Side::Buy, 29_999, 0.1)).unwrap();
+    assert_eq!(book.spread(), Some(2));
+
+    let outcome = book.add(order(4, Side::Buy, 30_002, 0.75)).unwrap();
+    assert_eq!(outcome.filled_qty(), 0.75);
+    assert_eq!(outcome.resting_qty, 0.);
+    assert_eq!(
+        book.best_ask().map(|i| (i.price(), i.qty())),
+        Some((OrderPrice::Limit(30_002), 1.0))
+    );
+    assert_eq!(book.mid(), Some(30_000.5));
+}
+
+#[test]
+fn queue_position_follows_the_queue() {
+    let mut book = OrderBook::new(0);
+    for (id, qty) in [(1, 5), (2, 3), (3, 2), (4, 7)] {
+        book.add(order(id, Side::Buy, 100, qty)).unwrap();
+    }
+    assert_eq!(book.queue_position(1), Ok((0, 0)));
+    assert_eq!(book.queue_position(4), Ok((3, 10)));
+
+    book.remove(2).unwrap();
+    assert_eq!(book.queue_position(4), Ok((2, 7)));
+
+    book.execute(&Exec(1, 4)).unwrap();
+    assert_eq!(book.queue_position(3), Ok((1, 1)));
+
+    // modifying the quantity sends the order to the back
+    book.change_qty(1, 2).unwrap();
+    assert_eq!(book.queue_position(1), Ok((2, 9)));
+    assert_eq!(book.queue_position(3), Ok((0, 0)));
+
+    book.set_crossing_policy(CrossingPolicy::Match);
+    book.add(order(5, Side::Sell, 100, 4)).unwrap();
+    assert!(!book.contains_order(3));
+    assert_eq!(book.queue_position(4), Ok((0, 0)));
+    assert_eq!(book.queue_position(1), Ok((1, 5)));
+    assert_eq!(
+        book.queue_position(3),
+        Err(OrderBookError::UnknownOrderId(3))
+    );
+}
+
+#[test]
+fn add_at_places_the_order_behind_rank_orders() {
+    let mut book = OrderBook::new(0);
+    book.add(order(1, Side::Buy, 100, 5)).unwrap();
+    book.add(order(2, Side::Buy, 100, 3)).unwrap();
+    book.add_at(order(3, Side::Buy, 100, 2), 0).unwrap();
+    book.add_at(order(4, Side::Buy, 100, 1), 2).unwrap();
+    // past the back of the queue
+    book.add_at(order(5, Side::Buy, 100, 4), 10).unwrap();
+    // a new price level
+    book.add_at(order(6, Side::Buy, 99, 4), 3).unwrap();
+
+    let level = book.iter_price_level(&Side::Buy).next().unwrap();
+    let ids: Vec<_> = level.iter_orders().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec![3, 1, 4, 2, 5]);
+    assert_eq!(book.queue_position(2), Ok((3, 8)));
+    assert_eq!(book.queue_position(6), Ok((0, 0)));
+    assert_eq!(book.best_bid().map(|i| i.qty()), Some(15));
+}
+
+#[test]
+fn price_level_rejects_duplicate_order_ids() {
+    let mut level = PriceLevel::new_with_order(order(1, Side::Buy, 100, 5));
+    level.add(order(2, Side::Buy, 100, 3)).unwrap();
+    assert_eq!(
+        level.add(order(1, Side::Buy, 100, 7)),
+        Err(OrderBookError::DuplicateOrderId(1))
+    );
+    assert_eq!(
+        level.insert_at(0, order(2, Side::Buy, 100, 7)),
+        Err(OrderBookError::DuplicateOrderId(2))
+    );
+    assert_eq!(level.price_qty().qty(), 8);
+    assert_eq!(level.order_count(), 2);
+    let queue: Vec<_> = level.iter_orders().map(|(id, o)| (*id, o.qty)).collect();
+    assert_eq!(queue, vec![(1, 5), (2, 3)]);
+}
+
+#[test]
+fn registry_routes_by_order_book_id_and_order_id() {
+    type Update = OrderBookUpdate<MakerOrder, u64, Exec>;
+    let mut registry = OrderBookRegistry::with_crossing_policy(CrossingPolicy::Match);
+    registry
+        .update(7, Update::Add(order(1, Side::Buy, 100, 5)))
+        .unwrap();
+    registry
+        .update(8, Update::Add(order(2, Side::Sell, 200, 3)))
+        .unwrap();
+    registry
+        .update(8, Update::Add(order(3, Side::Sell, 200, 1)))
+        .unwrap();
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.order_book_id_of(1), Some(7));
+    assert_eq!(registry.order_book_id_of(3), Some(8));
+    assert_eq!(
+        registry.update(8, Update::Add(order(1, Side::Buy, 100, 1))),
+        Err(OrderBookError::DuplicateOrderId(1))
+    );
+
+    // a cancel that only carries the order id
+    registry.remove(1).unwrap();
+    assert_eq!(registry.order_book_id_of(1), None);
+    assert_eq!(registry.get(7).unwrap().order_count(), 0);
+    assert_eq!(registry.remove(1), Err(OrderBookError::UnknownOrderId(1)));
+
+    registry.execute(&Exec(3, 1)).unwrap();
+    assert_eq!(registry.order_book_id_of(3), None);
+
+    // fully filled makers are dropped from the index
+    let outcome = registry
+        .update(8, Update::Add(order(4, Side::Buy, 200, 4)))
+        .unwrap();
+    assert_eq!(outcome.fills.len(), 1);
+    assert_eq!(registry.order_book_id_of(2), None);
+    assert_eq!(registry.order_book_id_of(4), Some(8));
+
+    // a partial cancel that only carries the order id
+    registry.change_qty(4, 2).unwrap();
+    assert_eq!(registry.order(4).map(|o| o.qty), Some(2));
+    assert_eq!(registry.get(8).unwrap().best_bid().unwrap().qty(), 2);
+    assert_eq!(
+        registry.change_qty(2, 1),
+        Err(OrderBookError::UnknownOrderId(2))
+    );
+    registry
+        .update(7, Update::Add(order(5, Side::Buy, 100, 1)))
+        .unwrap();
+    registry.change_qty(5, 0).unwrap();
+    assert_eq!(registry.order_book_id_of(5), None);
+    assert_eq!(registry.order(5), None);
+
+    registry.update(8, Update::Clear).unwrap();
+    assert_eq!(registry.order_book_id_of(4), None);
+    let mut counts: Vec<_> = registry
+        .iter()
+        .map(|(id, book)| (id, book.order_count()))
+        .collect();
+    counts.sort();
+    assert_eq!(counts, vec![(7, 0), (8, 0)]);
+}